@@ -1,22 +1,50 @@
-use spair::prelude::*;
+use spair::{Store, SubscriptionHandle, prelude::*};
 
 pub struct Child {
     value: i32,
+    value_from_parent: i32,
     callback_arg: CallbackArg<i32>,
+    // Clicks in all child components
+    clicks: Store<u32>,
+    total_clicks: u32,
+    _clicks_subscription: SubscriptionHandle,
 }
 
-impl Child {
-    pub fn new(callback_arg: CallbackArg<i32>) -> Self {
+impl spair::ComponentWithProps for Child {
+    type Props = i32;
+
+    fn with_props(comp: &Comp<Self>, value_from_parent: i32) -> Self {
+        // Provided by the parent component
+        let callback_arg = comp
+            .use_context::<CallbackArg<i32>>()
+            .expect("a callback to send values to the parent component");
+        let clicks = comp
+            .use_context::<Store<u32>>()
+            .expect("a store to count clicks in all child components");
+        let _clicks_subscription = clicks.subscribe(comp, |state: &mut Self, clicks| {
+            state.total_clicks = *clicks
+        });
         Self {
-            value: 42,
+            value: value_from_parent,
+            value_from_parent,
             callback_arg,
+            clicks,
+            total_clicks: 0,
+            _clicks_subscription,
         }
     }
 
-    pub fn set_value(&mut self, value: i32) {
-        self.value = value;
+    fn props_changed(&mut self, value_from_parent: i32) -> spair::ShouldRender {
+        if self.value_from_parent == value_from_parent {
+            return spair::ShouldRender::No;
+        }
+        self.value_from_parent = value_from_parent;
+        self.value = value_from_parent;
+        spair::ShouldRender::Yes
     }
+}
 
+impl Child {
     fn increment(&mut self) {
         self.value += 1;
         self.call_to_parent()
@@ -28,6 +56,7 @@ impl Child {
     }
 
     fn call_to_parent(&self) {
+        self.clicks.update(|clicks| *clicks += 1);
         if self.value % 5 == 0 {
             self.callback_arg.call(self.value);
         }
@@ -50,6 +79,7 @@ impl Child {
                 on_click = cc.comp.callback_arg(|state, _| state.increment()),
                 text("+"),
             ),
+            text(" Clicks in all child components: ", uc.state.total_clicks),
         )
     }
 }
@@ -1,12 +1,12 @@
-use spair::prelude::*;
+use spair::{Store, prelude::*};
 
 mod child;
-use child::Child;
 
 pub struct State {
     value: i32,
     value_from_child: Option<i32>,
-    child_comp: RcComp<Child>,
+    child_ids: Vec<u32>,
+    next_child_id: u32,
 }
 
 impl State {
@@ -18,16 +18,19 @@ impl State {
         self.value -= 1;
     }
 
-    pub fn receive_value_from_child(&mut self, value: i32) {
-        self.value_from_child = Some(value);
+    fn add_child(&mut self) {
+        self.child_ids.push(self.next_child_id);
+        self.next_child_id += 1;
+    }
+
+    fn remove_first_child(&mut self) {
+        if !self.child_ids.is_empty() {
+            self.child_ids.remove(0);
+        }
     }
 
-    fn send_value_to_child(&mut self) {
-        let value = self.value;
-        self.child_comp
-            .comp()
-            .callback_arg(Child::set_value)
-            .call(value);
+    pub fn receive_value_from_child(&mut self, value: i32) {
+        self.value_from_child = Some(value);
     }
 }
 
@@ -51,28 +54,43 @@ impl State {
                     on_click = cc.comp.callback_arg(|state, _| state.increment()),
                     text("+"),
                 ),
-                button(
-                    on_click = cc.comp.callback_arg(|state, _| state.send_value_to_child()),
-                    text("Send value to child component"),
-                ),
             ),
             div(text(
                 "Value received from child component: ",
                 uc.state.value_from_child.or_default("not yet"),
             )),
             hr(),
-            spair_comp_ref(cc.state.child_comp),
+            child::Child::props(uc.state.value),
+            hr(),
+            div(
+                button(
+                    on_click = cc.comp.callback_arg(|state, _| state.add_child()),
+                    text("Add a child component"),
+                ),
+                button(
+                    on_click = cc.comp.callback_arg(|state, _| state.remove_first_child()),
+                    text("Remove the first child component"),
+                ),
+            ),
+            div(spair_comp_list(
+                uc.state.child_ids.iter(),
+                |id| -> &u32 { id },
+                |_id| child::Child::props(uc.state.value),
+            )),
         )
     }
 }
 
 pub fn main() {
     wasm_logger::init(wasm_logger::Config::default());
-    spair::start_app(|app_comp| State {
-        value: 42,
-        value_from_child: None,
-        child_comp: RcComp::new(|_child_comp| {
-            Child::new(app_comp.callback_arg(State::receive_value_from_child))
-        }),
+    spair::start_app(|app_comp| {
+        app_comp.provide_context(app_comp.callback_arg(State::receive_value_from_child));
+        app_comp.provide_context(Store::new(0u32));
+        State {
+            value: 42,
+            value_from_child: None,
+            child_ids: Vec::new(),
+            next_child_id: 1,
+        }
     });
 }
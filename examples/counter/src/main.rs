@@ -1,8 +1,9 @@
 use spair::prelude::*;
-use spair::{CallbackArg, web_sys::MouseEvent};
+use spair::{CallbackArg, ShouldRender, web_sys::MouseEvent};
 
 struct AppState {
-    value: i32,
+    // Only the text that reads `value` is updated when it changes
+    value: Queued<i32>,
 }
 
 #[create_view]
@@ -15,23 +16,26 @@ impl UpdownButton {
 }
 
 impl AppState {
-    fn increase(&mut self) {
-        self.value += 1;
+    fn increase(&mut self) -> ShouldRender {
+        self.value.set(*self.value + 1);
+        ShouldRender::No
     }
 
-    fn decrease(&mut self) {
-        self.value -= 1;
+    fn decrease(&mut self) -> ShouldRender {
+        self.value.set(*self.value - 1);
+        ShouldRender::No
     }
 }
 #[impl_component]
 impl AppState {
     fn create(ccontext: &Context<Self>) {}
+    #[queued(value)]
     fn update(ucontext: &Context<Self>) {}
     fn view() {
         div(
             replace_at_element_id = "root",
             UpdownButton(ccontext.comp.callback_arg(|state, _| state.decrease()), "-"),
-            ucontext.state.value,
+            *ucontext.state.value,
             UpdownButton(ccontext.comp.callback_arg(|state, _| state.increase()), "+"),
         )
     }
@@ -39,5 +43,7 @@ impl AppState {
 
 fn main() {
     // wasm_logger::init(wasm_logger::Config::default());
-    spair::start_app(|_| AppState { value: 42 });
+    spair::start_app(|_| AppState {
+        value: Queued::new(42),
+    });
 }
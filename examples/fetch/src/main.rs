@@ -1,6 +1,6 @@
 use gloo_net::http;
 use serde::{Deserialize, Serialize};
-use spair::prelude::*;
+use spair::{Resource, ResourceState, prelude::*};
 
 /// A struct to hold some data from the github Branch API.
 ///
@@ -31,36 +31,7 @@ pub struct Signature {
 }
 
 struct State {
-    comp: spair::Comp<Self>,
-    branch: Option<Branch>,
-    message: String,
-}
-
-impl State {
-    fn set_data(&mut self, branch: Branch) {
-        self.branch = Some(branch);
-        self.message = "".to_string();
-    }
-
-    fn reset(&mut self) {
-        self.branch = None;
-        self.message = "Wait for your click".to_string();
-    }
-
-    fn start_fetching(&mut self) {
-        self.message = "Clicked! Please wait for a moment".to_string();
-        let callback = self
-            .comp
-            .callback_arg(|state: &mut Self, result| match result {
-                Ok(branch) => state.set_data(branch),
-                Err(err) => state.fetch_error(err),
-            });
-        fetch_repo_metadata().spawn_local_with_callback(callback);
-    }
-
-    fn fetch_error(&mut self, e: gloo_net::Error) {
-        self.message = e.to_string();
-    }
+    branch: Resource<Branch, gloo_net::Error>,
 }
 
 async fn fetch_repo_metadata() -> Result<Branch, gloo_net::Error> {
@@ -79,28 +50,40 @@ impl State {
     fn view() {
         div(
             replace_at_element_id = "root",
-            match ucontext.state.branch.as_ref() {
-                Some(branch) => {
+            match ucontext.state.branch.state() {
+                ResourceState::Idle => {
+                    fn_create(ccontext = ucontext);
+                    button(
+                        on_click = ccontext.comp.callback_arg(|state, _| state.branch.fetch()),
+                        text("Click to fetch the latest commit info of the wasm-bindgen repo"),
+                    )
+                }
+                ResourceState::Loading => p(text("Clicked! Please wait for a moment")),
+                ResourceState::Ready(branch) => {
                     fn_create(ccontext = ucontext);
                     div(
                         BranchView(branch),
                         button(
-                            on_click = ccontext.comp.callback_arg(|state, _| state.reset()),
+                            on_click = ccontext
+                                .comp
+                                .callback_arg(|state, _| state.branch.invalidate()),
                             text("Reset"),
                         ),
                     )
                 }
-                None => {
+                ResourceState::Failed(e) => {
                     fn_create(ccontext = ucontext);
-                    button(
-                        on_click = ccontext
-                            .comp
-                            .callback_arg(|state, _| state.start_fetching()),
-                        text("Click to fetch the latest commit info of the wasm-bindgen repo"),
+                    div(
+                        p(text(e.to_string())),
+                        button(
+                            on_click = ccontext
+                                .comp
+                                .callback_arg(|state, _| state.branch.refetch()),
+                            text("Retry"),
+                        ),
                     )
                 }
             },
-            p(text(&ucontext.state.message)),
         )
     }
 }
@@ -126,8 +109,10 @@ impl BranchView {
 
 pub fn main() {
     spair::start_app(|comp| State {
-        comp: comp.clone(),
-        branch: None,
-        message: "Wait for your click...".to_string(),
+        branch: Resource::new(
+            &comp,
+            |state: &mut State| &mut state.branch,
+            fetch_repo_metadata,
+        ),
     });
 }
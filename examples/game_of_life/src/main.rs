@@ -1,9 +1,8 @@
-use gloo_timers::callback::Interval;
 use rand::Rng;
 use spair::{
-    CallbackArg, Context,
+    CallbackArg, Comp, Context, ListenerHandle, TimerHandle,
     prelude::{create_view, impl_component},
-    web_sys::MouseEvent,
+    web_sys::{KeyboardEvent, MouseEvent},
 };
 
 use crate::cell::Cellule;
@@ -11,11 +10,12 @@ use crate::cell::Cellule;
 mod cell;
 
 pub struct App {
-    active: bool,
+    comp: Comp<Self>,
+    interval: Option<TimerHandle>,
+    _shortcuts: ListenerHandle,
     cellules: Vec<Cellule>,
     cellules_width: usize,
     cellules_height: usize,
-    _interval: Interval,
 }
 
 impl App {
@@ -86,7 +86,7 @@ impl App {
     }
 
     fn start(&mut self) -> spair::ShouldRender {
-        self.active = true;
+        self.interval = Some(self.comp.set_interval(200, App::tick));
         log::info!("Start");
         spair::ShouldRender::No
     }
@@ -97,23 +97,31 @@ impl App {
     }
 
     fn stop(&mut self) -> spair::ShouldRender {
-        self.active = false;
+        self.interval = None;
         log::info!("Stop");
         spair::ShouldRender::No
     }
 
+    /// Space to start/stop, `s` to step
+    fn handle_shortcut(&mut self, event: KeyboardEvent) -> spair::ShouldRender {
+        match event.key().as_str() {
+            " " if self.interval.is_some() => self.stop(),
+            " " => self.start(),
+            "s" => {
+                self.step();
+                spair::ShouldRender::Yes
+            }
+            _ => spair::ShouldRender::No,
+        }
+    }
+
     fn toggle_cellule(&mut self, index: usize) {
         let cellule = self.cellules.get_mut(index).unwrap();
         cellule.toggle();
     }
 
-    fn tick(&mut self) -> spair::ShouldRender {
-        if self.active {
-            self.step();
-            spair::ShouldRender::Yes
-        } else {
-            spair::ShouldRender::No
-        }
+    fn tick(&mut self) {
+        self.step();
     }
 }
 
@@ -233,17 +241,16 @@ impl Button {
 }
 
 fn main() {
+    spair::set_default_render_mode(spair::RenderMode::AnimationFrame);
     spair::start_app(|comp| {
-        let callback = comp.callback(App::tick);
-        let interval = Interval::new(200, move || callback.call());
-
         let (cellules_width, cellules_height) = (53, 40);
         App {
-            active: false,
+            _shortcuts: comp.listen_document("keydown", App::handle_shortcut),
+            comp,
+            interval: None,
             cellules: vec![Cellule::new_dead(); cellules_width * cellules_height],
             cellules_width,
             cellules_height,
-            _interval: interval,
         }
     });
 }
@@ -1,8 +1,8 @@
 use std::ops::Not;
 
-use gloo_storage::{LocalStorage, Storage};
 use serde::{Deserialize, Serialize};
 use spair::{
+    Persisted, WsRef,
     prelude::*,
     web_sys::{EventTarget, FocusEvent, HtmlInputElement, KeyboardEvent},
 };
@@ -22,17 +22,23 @@ struct TodoItem {
 }
 
 pub struct App {
-    data: TodoList,
+    data: Persisted<TodoList>,
 
     filter: Filter,
     editing_id: Option<u32>,
+    focus_edit_input: bool,
+    edit_input: WsRef<HtmlInputElement>,
     new_todo_title: String,
 }
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, spair::Route)]
 enum Filter {
+    #[route("#/all")]
+    #[not_found]
     All,
+    #[route("#/active")]
     Active,
+    #[route("#/completed")]
     Completed,
 }
 
@@ -59,13 +65,14 @@ impl App {
         if title.is_empty() {
             return;
         }
-        self.data.items.push(TodoItem {
-            id: self.data.next_id,
+        let mut data = self.data.edit();
+        let id = data.next_id;
+        data.items.push(TodoItem {
+            id,
             title,
             completed: false,
         });
-        self.data.next_id += 1;
-        write_data_to_storage(&self.data);
+        data.next_id += 1;
     }
 
     fn visible_items(&self) -> impl Iterator<Item = &TodoItem> {
@@ -82,31 +89,31 @@ impl App {
     fn toggle_all(&mut self) {
         let checked = !self.is_all_completed();
         self.data
+            .edit()
             .items
             .iter_mut()
             .for_each(|item| item.completed = checked);
-        write_data_to_storage(&self.data);
     }
 
     fn toggle(&mut self, id: u32) {
-        if let Some(item) = self.data.items.iter_mut().find(|item| item.id == id) {
-            item.completed = !item.completed;
-            write_data_to_storage(&self.data);
-        }
+        let Some(index) = self.data.items.iter().position(|item| item.id == id) else {
+            return;
+        };
+        let mut data = self.data.edit();
+        data.items[index].completed = !data.items[index].completed;
     }
 
     fn clear_completed(&mut self) {
-        self.data.items.retain(|item| !item.completed);
-        write_data_to_storage(&self.data);
+        self.data.edit().items.retain(|item| !item.completed);
     }
 
     fn remove(&mut self, id: u32) {
-        self.data.items.retain(|item| item.id != id);
-        write_data_to_storage(&self.data);
+        self.data.edit().items.retain(|item| item.id != id);
     }
 
     fn start_editing(&mut self, id: u32) {
         self.editing_id = Some(id);
+        self.focus_edit_input = true;
     }
 
     fn end_editing(&mut self, target: Option<EventTarget>) {
@@ -125,12 +132,12 @@ impl App {
         }) {
             Some(title) => {
                 self.data
+                    .edit()
                     .items
                     .iter_mut()
                     .find(|item| item.id == id)
                     .expect_throw("Why editing item with an invalid id?")
                     .title = title.to_string();
-                write_data_to_storage(&self.data);
             }
             None => self.remove(id),
         }
@@ -144,42 +151,15 @@ impl App {
 
 const TODO_DATA_KEY: &str = "spair-todos-list";
 
-pub(crate) fn write_data_to_storage(data: &TodoList) {
-    LocalStorage::set(TODO_DATA_KEY, data).expect_throw("Unable to set item on local storage")
-}
-
-pub(crate) fn read_data_from_storage() -> TodoList {
-    LocalStorage::get(TODO_DATA_KEY).unwrap_or_default()
-}
-
-impl spair::Route for Filter {
-    fn from_location(location: &spair::web_sys::Location) -> Self {
-        if let Ok(hash) = location.hash() {
-            match hash.as_str() {
-                "#/all" => Self::All,
-                "#/active" => Self::Active,
-                "#/completed" => Self::Completed,
-                _ => Self::All,
-            }
-        } else {
-            Self::All
-        }
-    }
-
-    fn url(&self) -> String {
-        match self {
-            Filter::All => "#/all",
-            Filter::Active => "#/active",
-            Filter::Completed => "#/completed",
-        }
-        .to_string()
-    }
-}
-
 #[impl_component]
 impl App {
     fn create(cc: &Context<Self>) {}
     fn update(uc: &Context<Self>) {}
+    fn rendered(&mut self, _comp: &Comp<Self>, _first: bool) {
+        if std::mem::take(&mut self.focus_edit_input) {
+            self.edit_input.execute(|input| input.focus());
+        }
+    }
     fn view() {
         section(
             replace_at_element_id = "root",
@@ -219,12 +199,9 @@ impl Header {
                 autofocus = true,
                 placeholder = "What needs to be done?",
                 value = new_todo_title,
-                on_keydown = ccomp.callback_arg(|state, event: KeyboardEvent| {
-                    let key_code = event.code();
-                    if key_code == ENTER_KEY {
-                        state.create_new_todo();
-                    }
-                }),
+                on_keydown = ccomp
+                    .callback_arg(|state, _: ()| state.create_new_todo())
+                    .filter_map(|event: KeyboardEvent| (event.code() == ENTER_KEY).then_some(())),
                 on_input_string =
                     ccomp.callback_arg(|state, value| state.set_new_todo_title(value)),
             ),
@@ -330,6 +307,7 @@ impl TodoListView {
                         true => input(
                             class = "edit",
                             autofocus = true,
+                            set_node_ref_to = &uc.state.edit_input,
                             value = &uitem.title,
                             on_blur = uc.comp.callback_arg(move |state, event: FocusEvent| {
                                 state.end_editing(event.current_target())
@@ -353,11 +331,15 @@ impl TodoListView {
 fn main() {
     wasm_logger::init(wasm_logger::Config::default());
     spair::start_app_with_routing(
-        |_| App {
+        |comp| App {
             filter: Filter::All,
             editing_id: None,
+            focus_edit_input: false,
+            edit_input: WsRef::none(),
             new_todo_title: String::new(),
-            data: read_data_from_storage(),
+            // Changes from other tabs are synced into this app
+            data: Persisted::<TodoList>::local(TODO_DATA_KEY)
+                .sync(&comp, |state: &mut App| &mut state.data),
         },
         App::set_filter,
     );
@@ -0,0 +1,57 @@
+use spair::prelude::*;
+
+struct Counter {
+    label: &'static str,
+    value: i32,
+}
+
+#[derive(Clone)]
+enum Msg {
+    Increase,
+    Set(i32),
+}
+
+impl spair::MessageComponent for Counter {
+    type Msg = Msg;
+
+    fn handle(&mut self, msg: Msg) -> spair::ShouldRender {
+        match msg {
+            Msg::Increase => self.value += 1,
+            Msg::Set(value) => self.value = value,
+        }
+        spair::ShouldRender::Yes
+    }
+}
+
+#[impl_component]
+impl Counter {
+    fn create(ccontext: &Context<Self>) {}
+    fn update(ucontext: &Context<Self>) {}
+    fn view() {
+        div(
+            text(ccontext.state.label, ": ", ucontext.state.value),
+            button(on_click = ccontext.comp.msg(Msg::Increase), text("+")),
+        )
+    }
+}
+
+fn main() {
+    // Each widget is mounted into its own element of an existing page
+    let likes = spair::mount_at_selector("#likes", |_| Counter {
+        label: "Likes",
+        value: 0,
+    });
+    let stars = spair::mount_at_selector("#stars", |_| Counter {
+        label: "Stars",
+        value: 0,
+    });
+
+    if let Some(stars) = stars {
+        // Call into the component from outside
+        stars.comp().send(Msg::Set(42));
+        stars.forget();
+    }
+    if let Some(likes) = likes {
+        likes.forget();
+    }
+}
@@ -5,24 +5,43 @@ use syn::{Block, Ident, ImplItem, ItemImpl, Result, ReturnType};
 use crate::{
     MultiErrors,
     dom::{Element, Item, LastNode, SubMod},
+    queue_render::QueueRender,
     view::{View, insert_use_spair_items_to_fn},
 };
 
+// Optional fns of `::spair::Component` that users can add to an `#[impl_component]`
+const LIFECYCLE_FNS: &[&str] = &["mounted", "rendered", "dispose"];
+
 pub struct Component {
     _component_name: Ident,
     view_state_name: Ident,
     item_impl: ItemImpl,
     element: Element,
+    queue_render: Option<QueueRender>,
+    lifecycle_fns: Vec<ImplItem>,
 
     sub_mod: SubMod,
 }
 impl Component {
-    pub fn from_item_impl(item_impl: syn::ItemImpl) -> Result<Self> {
+    pub fn from_item_impl(mut item_impl: syn::ItemImpl) -> Result<Self> {
+        let mut errors = MultiErrors::default();
+        let lifecycle_fns = take_lifecycle_fns(&mut item_impl);
+        let queue_render = item_impl.items.iter_mut().find_map(|item| match item {
+            ImplItem::Fn(fn_update) if fn_update.sig.ident == "update" => {
+                QueueRender::take_from_fn_update(fn_update, &mut errors)
+            }
+            _ => None,
+        });
+        errors.report_error()?;
         let view = View::from_item_impl(item_impl)?;
-        Component::from_view(view)
+        Component::from_view(view, queue_render, lifecycle_fns)
     }
 
-    fn from_view(view: View) -> Result<Self> {
+    fn from_view(
+        view: View,
+        queue_render: Option<QueueRender>,
+        lifecycle_fns: Vec<ImplItem>,
+    ) -> Result<Self> {
         let mut errors = MultiErrors::default();
         let View {
             view_name,
@@ -55,6 +74,12 @@ impl Component {
                 Item::CompRef(value) => {
                     errors.error_at2(value.first_span(), message, "a spair_comp_ref here")
                 }
+                Item::ChildComp(value) => {
+                    errors.error_at2(value.first_span(), message, "a child component here")
+                }
+                Item::CompList(value) => {
+                    errors.error_at2(value.first_span(), message, "a spair_comp_list here")
+                }
             }
         };
         for item in items {
@@ -68,6 +93,8 @@ impl Component {
             view_state_name: view_state,
             item_impl,
             element,
+            queue_render,
+            lifecycle_fns,
             sub_mod,
         })
     }
@@ -92,6 +119,15 @@ impl Component {
                 .expect("get fn create for component"),
         );
 
+        // fn update_queued(view_state: &mut Self::ViewState, ...) {...}
+        // must be generated from the user's fn update before it is modified
+        let fn_update_queued = self.generate_fn_update_queued(
+            impl_view_state
+                .items
+                .last()
+                .expect("get fn update for component"),
+        );
+
         // fn update(view_state: &mut Self::ViewState, ...) {...}
         self.generate_update_fn(
             impl_view_state
@@ -99,6 +135,10 @@ impl Component {
                 .last_mut()
                 .expect("get fn update for component"),
         );
+        impl_view_state.items.extend(fn_update_queued);
+        impl_view_state
+            .items
+            .extend(self.lifecycle_fns.iter().cloned());
 
         // modify impl to make it like:
         // impl ::spair::Component for ComponentName {
@@ -144,6 +184,15 @@ impl Component {
         item_impl
             .items
             .insert(0, syn::ImplItem::Type(impl_item_type));
+
+        if self.queue_render.is_some() {
+            let impl_item_const = quote! {const QUEUE_RENDER: bool = true;};
+            let impl_item_const: syn::ImplItemConst = syn::parse(impl_item_const.into())
+                .expect("const QUEUE_RENDER: bool = true; for component");
+            item_impl
+                .items
+                .insert(1, syn::ImplItem::Const(impl_item_const));
+        }
     }
 
     fn generate_fn_create(&self, fn_create: &mut ImplItem) {
@@ -216,11 +265,49 @@ impl Component {
         fn_update.sig.inputs.insert(0, view_state_arg);
 
         let update_code = self.element.generate_fn_update(&self.sub_mod, &view_state);
+        let marking_rendered = self
+            .queue_render
+            .as_ref()
+            .map(|v| v.generate_marking_rendered());
         let update_code = quote! {{
             #update_code
+            #marking_rendered
         }};
         let update_code: Block =
             syn::parse(update_code.into()).expect("fn update code for component");
         fn_update.block.stmts.extend(update_code.stmts);
     }
+
+    fn generate_fn_update_queued(&self, fn_update: &ImplItem) -> Option<ImplItem> {
+        let queue_render = self.queue_render.as_ref()?;
+        let ImplItem::Fn(fn_update) = fn_update else {
+            return None;
+        };
+        let view_state = Ident::new("_spair_self_this_me_view_state_", Span::call_site());
+        let mut snippets = Vec::new();
+        self.element
+            .collect_fn_update_snippets(&self.sub_mod, &view_state, &mut snippets);
+        let fn_update_queued = queue_render.generate_fn_update_queued(
+            fn_update,
+            &view_state,
+            &self.view_state_name,
+            snippets,
+        );
+        Some(syn::parse(fn_update_queued.into()).expect("fn update_queued for component"))
+    }
+}
+
+fn take_lifecycle_fns(item_impl: &mut ItemImpl) -> Vec<ImplItem> {
+    let is_lifecycle_fn = |item: &ImplItem| match item {
+        ImplItem::Fn(item_fn) => LIFECYCLE_FNS.iter().any(|name| item_fn.sig.ident == name),
+        _ => false,
+    };
+    let lifecycle_fns = item_impl
+        .items
+        .iter()
+        .filter(|item| is_lifecycle_fn(item))
+        .cloned()
+        .collect();
+    item_impl.items.retain(|item| !is_lifecycle_fn(item));
+    lifecycle_fns
 }
@@ -0,0 +1,103 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::{Expr, Ident, Path, spanned::Spanned};
+
+use super::{LastNode, stage::Stage};
+
+/// A child component that is created from props: `ChildName::props(props_expr)`, the name
+/// can be a path like `module::ChildName`
+pub struct ChildComp {
+    comp_name: Path,
+    props: Expr,
+    stage: Stage,
+
+    spair_ident: Ident,
+    spair_ident_marker: Ident,
+}
+
+impl std::fmt::Debug for ChildComp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}::props", self.comp_name.to_token_stream())
+    }
+}
+
+impl ChildComp {
+    pub fn new(
+        comp_name: Path,
+        props: Expr,
+        stage_picker: &super::stage::StagePicker,
+        item_counter: &mut super::ItemCounter,
+    ) -> Self {
+        let stage = stage_picker.stage_of(&props);
+        Self {
+            comp_name,
+            props,
+            stage,
+            spair_ident: item_counter.new_ident("_child_comp_"),
+            spair_ident_marker: item_counter.new_ident_marker("_child_comp_"),
+        }
+    }
+
+    pub fn first_span(&self) -> proc_macro2::Span {
+        self.comp_name.span()
+    }
+
+    pub fn generate_view_state_struct_fields(&self) -> TokenStream {
+        let ident = &self.spair_ident;
+        let comp_name = &self.comp_name;
+        quote! {pub #ident: ::spair::ChildComp<#comp_name>,}
+    }
+
+    pub fn generate_html_string(&self, html_string: &mut String) {
+        html_string.push_str("<!--child-comp-->")
+    }
+
+    pub fn generate_fn_create(&self, last_node: &LastNode) -> TokenStream {
+        let parent = &last_node.parent;
+        let ident = &self.spair_ident;
+        let marker = &self.spair_ident_marker;
+        let get_marker = last_node.get_ws_node(&self.spair_ident_marker);
+        // Props that only available in the update stage will be given in the first update
+        let create_child_comp = if self.stage == Stage::Update {
+            quote! {::spair::ChildComp::new(#marker.get_ws_node_ref().clone())}
+        } else {
+            let props = &self.props;
+            quote! {::spair::ChildComp::with_props(&#parent, #marker.get_ws_node_ref().clone(), #props)}
+        };
+
+        quote! {
+            #get_marker
+            let #ident = #create_child_comp;
+            let #marker = &#ident.comp_marker;
+        }
+    }
+
+    pub fn spair_indent_to_get_next_node(&self) -> &Ident {
+        &self.spair_ident_marker
+    }
+
+    pub fn generate_fn_create_return_value(&self) -> TokenStream {
+        let ident = &self.spair_ident;
+        quote! {
+            #ident,
+        }
+    }
+
+    pub fn generate_fn_update(&self, view_state: &Ident, parent: &Ident) -> TokenStream {
+        if self.stage != Stage::Update {
+            return TokenStream::new();
+        }
+        let ident = &self.spair_ident;
+        let props = &self.props;
+        quote! {
+            #view_state.#ident.update(&#view_state.#parent, #props);
+        }
+    }
+
+    pub fn generate_fn_remove_from(&self, parent: &Ident) -> TokenStream {
+        let ident = &self.spair_ident;
+        quote! {
+            self.#ident.remove_from(#parent);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::ops::Not;
+
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::{Expr, ExprClosure, Ident, Type, spanned::Spanned, token};
+
+use crate::MultiErrors;
+
+use super::{
+    LastNode, child_comp_path, is_child_comp_call,
+    list::KeyedListItems,
+    stage::{Stage, StagePicker},
+};
+
+/// A keyed list of child components:
+/// `spair_comp_list(items_iterator, |item| -> &KeyType {&item.key}, |item| ChildName::props(...))`
+pub struct CompList {
+    spair_comp_list_keyword: Ident,
+    stage: Stage,
+    items_iterator: Expr,
+    key_type_name: Box<Type>,
+    get_key_closure: ExprClosure,
+    comp_name: syn::Path,
+    get_props_closure: ExprClosure,
+    partial_list: bool,
+
+    spair_ident: Ident,
+    spair_ident_marker: Ident,
+}
+
+impl std::fmt::Debug for CompList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "comp list of {}", self.comp_name.to_token_stream())
+    }
+}
+
+impl CompList {
+    pub fn new(
+        at_root: bool,
+        spair_comp_list_keyword: Ident,
+        paren_token: token::Paren,
+        args: syn::punctuated::Punctuated<Expr, syn::token::Comma>,
+        stage_picker: &StagePicker,
+        item_counter: &mut super::ItemCounter,
+        errors: &mut MultiErrors,
+    ) -> Option<Self> {
+        if at_root {
+            errors.error_at(spair_comp_list_keyword.span(), "Not allow at root level");
+        }
+        if args.len() != 3 {
+            errors.error_at(
+                paren_token.span.join(),
+                "Expected 3 items like `spair_comp_list(list_iterator, |item| -> &KeyType {&item.key}, |item| ChildName::props(...))`",
+            );
+            return None;
+        }
+        let mut args = args.into_iter();
+        let items_iterator = args.next()?;
+        let get_key_closure = args.next()?;
+        let get_props_closure = args.next()?;
+
+        let KeyedListItems {
+            key_type_name,
+            get_key_closure,
+        } = KeyedListItems::from_closure(get_key_closure, errors)?;
+
+        let message = "Expected a closure that returns the props of a child component like: spair_comp_list(..., |item| ChildName::props(...))";
+        let Expr::Closure(mut get_props_closure) = get_props_closure else {
+            errors.error_at(get_props_closure.span(), message);
+            return None;
+        };
+        let comp_name = match get_props_closure.body.as_ref() {
+            Expr::Call(expr_call) if is_child_comp_call(expr_call) && expr_call.args.len() == 1 => {
+                let Expr::Path(expr_path) = expr_call.func.as_ref() else {
+                    return None;
+                };
+                let comp_name = child_comp_path(&expr_path.path);
+                let props = expr_call.args.first().cloned()?;
+                *get_props_closure.body = props;
+                comp_name
+            }
+            other => {
+                errors.error_at(other.span(), message);
+                return None;
+            }
+        };
+
+        let stage = stage_picker.stage_of(&items_iterator);
+        Some(CompList {
+            spair_comp_list_keyword,
+            stage,
+            items_iterator,
+            key_type_name,
+            get_key_closure,
+            comp_name,
+            get_props_closure,
+            partial_list: false,
+            spair_ident: item_counter.new_ident("_comp_list_"),
+            spair_ident_marker: item_counter.new_ident_marker("comp_list"),
+        })
+    }
+
+    pub fn first_span(&self) -> proc_macro2::Span {
+        self.spair_comp_list_keyword.span()
+    }
+
+    pub fn prepare_items_for_generating_code(&mut self, parent_has_only_one_child: bool) {
+        self.partial_list = parent_has_only_one_child.not();
+    }
+
+    pub fn generate_view_state_struct_fields(&self) -> TokenStream {
+        let ident = &self.spair_ident;
+        let key_type_name = &self.key_type_name;
+        let comp_name = &self.comp_name;
+        quote! {pub #ident: ::spair::CompList<#key_type_name, #comp_name>,}
+    }
+
+    pub fn generate_html_string(&self, html_string: &mut String) {
+        if self.partial_list {
+            html_string.push_str("<!--icomplist-->");
+        }
+    }
+
+    pub fn generate_fn_create(&self, last_node: &LastNode) -> TokenStream {
+        let parent = &last_node.parent;
+        let ident = &self.spair_ident;
+
+        let marker_ident = &self.spair_ident_marker;
+        let get_end_node = if self.partial_list {
+            let get_marker = last_node.get_ws_node(&self.spair_ident_marker);
+            quote! {
+                #get_marker
+                let #marker_ident = Some(#marker_ident.get_ws_node_ref().clone());
+            }
+        } else {
+            quote! {
+                let #marker_ident = None;
+            }
+        };
+
+        let render_at_creation = if self.stage == Stage::Creation {
+            let update_call = self.generate_update_call();
+            quote! {
+                let mut #ident = #ident;
+                #ident #update_call
+            }
+        } else {
+            quote! {}
+        };
+
+        quote! {
+            #get_end_node
+            let #ident = ::spair::CompList::new(&#parent, #marker_ident);
+            #render_at_creation
+            let #marker_ident = #ident.end_node();
+        }
+    }
+
+    fn generate_update_call(&self) -> TokenStream {
+        let items_iterator = &self.items_iterator;
+        let get_key_closure = &self.get_key_closure;
+        let get_props_closure = &self.get_props_closure;
+        quote! {
+            .update(#items_iterator, #get_key_closure, #get_props_closure);
+        }
+    }
+
+    pub fn spair_indent_to_get_next_node(&self) -> &Ident {
+        &self.spair_ident_marker
+    }
+
+    pub fn generate_fn_create_return_value(&self) -> TokenStream {
+        let ident = &self.spair_ident;
+        quote! {#ident,}
+    }
+
+    pub fn generate_fn_update(&self, view_state: &Ident) -> TokenStream {
+        if self.stage != Stage::Update {
+            return quote! {};
+        }
+        let ident = &self.spair_ident;
+        let update_call = self.generate_update_call();
+        quote! {
+            #view_state.#ident #update_call
+        }
+    }
+}
@@ -164,7 +164,7 @@ impl Attribute {
                     }
                 }
             }
-            SET_NODE_REF_TO => quote! {#attribute_value.set(&#element);},
+            SET_NODE_REF_TO => quote! {(#attribute_value).set(&#element);},
             HREF_STR => quote! {#element.set_str_attribute("href",#attribute_value);},
             INNER_HTML => quote! {#element.unsafely_set_inner_html(#attribute_value);},
             "id" => quote! {#element.set_id(#attribute_value);},
@@ -225,6 +225,8 @@ impl Attribute {
         }
         match self.attribute_name_in_string.as_str() {
             REPLACE_AT_ELEMENT_ID => quote! {},
+            // The element may be re-created in the update stage, e.g. in a `match` arm
+            SET_NODE_REF_TO => quote! {(#attribute_value).set(&#element);},
             HREF_STR => {
                 quote! {#element.set_str_attribute_at_index(#index, "href", #attribute_value);}
             }
@@ -788,6 +790,8 @@ impl Element {
                     Item::List(_value) => false,
                     Item::Match(_value) => true,
                     Item::CompRef(_value) => true,
+                    Item::ChildComp(_value) => true,
+                    Item::CompList(_value) => false,
                 }
             };
         }
@@ -956,6 +960,33 @@ impl Element {
         }
     }
 
+    /// Same as `generate_fn_update`, but each piece of update code is kept separately.
+    pub fn collect_fn_update_snippets(
+        &self,
+        sub_mod: &SubMod,
+        view_state: &Ident,
+        snippets: &mut Vec<TokenStream>,
+    ) {
+        let element = &self.spair_ident;
+        let element_tokens = quote! {#view_state.#element};
+        let element_name = self.html_tag_ident.to_string();
+        snippets.extend(
+            self.events
+                .iter()
+                .chain(self.attributes.iter())
+                .map(|v| v.generate_view_state_fn_update_code(&element_name, &element_tokens)),
+        );
+        for item in self.children.items.iter() {
+            match item {
+                Item::Element(value) => {
+                    value.collect_fn_update_snippets(sub_mod, view_state, snippets)
+                }
+                _ => snippets.push(item.generate_fn_update(sub_mod, view_state, &self.spair_ident)),
+            }
+        }
+        snippets.push(self.generate_fn_update_for_select_element(view_state));
+    }
+
     fn generate_fn_update_for_attributes(&self, view_state: &Ident) -> TokenStream {
         let element = &self.spair_ident;
 
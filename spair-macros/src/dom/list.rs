@@ -30,9 +30,9 @@ pub struct List {
     spair_ident_marker: Ident,
 }
 
-struct KeyedListItems {
-    key_type_name: Box<Type>,
-    get_key_closure: ExprClosure,
+pub struct KeyedListItems {
+    pub key_type_name: Box<Type>,
+    pub get_key_closure: ExprClosure,
 }
 
 impl std::fmt::Debug for List {
@@ -394,7 +394,7 @@ impl List {
 }
 
 impl KeyedListItems {
-    fn from_closure(expr: Expr, errors: &mut MultiErrors) -> Option<KeyedListItems> {
+    pub fn from_closure(expr: Expr, errors: &mut MultiErrors) -> Option<KeyedListItems> {
         let Expr::Closure(closure) = expr else {
             errors.error_at(
                 expr.span(),
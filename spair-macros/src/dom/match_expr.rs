@@ -1,7 +1,7 @@
 use std::ops::Not;
 
 use proc_macro2::{Span, TokenStream};
-use quote::quote;
+use quote::{ToTokens, quote};
 use syn::{Expr, ExprMatch, Ident, spanned::Spanned, token::Brace};
 
 use crate::MultiErrors;
@@ -119,13 +119,13 @@ impl Match {
         let marker = match self.parent_has_only_one_child {
             true => quote! {},
             false => {
-                quote! { marker: ::spair::WsNode, }
+                quote! { pub marker: ::spair::WsNode, }
             }
         };
         let match_state_struct = quote! {
             pub struct #struct_name{
                 pub view_state: #enum_name,
-                pub #marker
+                #marker
             }
 
             impl #struct_name {
@@ -236,7 +236,7 @@ impl Match {
     }
 
     fn generate_fn_create_code(&self, sub_mod: &SubMod, parent_of_the_match: &Ident) -> ExprMatch {
-        let view_state = &self.spair_ident;
+        let next_sibling = self.generate_next_sibling(&self.spair_ident.to_token_stream());
         let mut expr_match = self.expr_match.clone();
         for (arm, arm_view) in expr_match.arms.iter_mut().zip(self.arm_views.iter()) {
             let closure_definition =
@@ -244,7 +244,7 @@ impl Match {
             let closure_name = &arm_view.fn_create_ident;
             let arm_code = quote! {{
                 #closure_definition
-                #closure_name(&#parent_of_the_match, Some(#view_state.marker.get_ws_node_ref()))
+                #closure_name(&#parent_of_the_match, #next_sibling)
             }};
             let arm_code: syn::Block =
                 syn::parse(arm_code.into()).expect("fn create match arm code");
@@ -257,6 +257,16 @@ impl Match {
         expr_match
     }
 
+    // The node that the arm's nodes are inserted before, there is no marker when the match is
+    // the only child of its parent.
+    fn generate_next_sibling(&self, match_view_state: &TokenStream) -> TokenStream {
+        if self.parent_has_only_one_child {
+            quote! {None::<&::spair::web_sys::Node>}
+        } else {
+            quote! {Some(#match_view_state.marker.get_ws_node_ref())}
+        }
+    }
+
     pub fn spair_indent_to_get_next_node(&self) -> &Ident {
         &self.spair_ident_marker
     }
@@ -277,10 +287,12 @@ impl Match {
         }
         let match_view_state = &self.spair_ident;
         let match_view_state = quote! {#view_state.#match_view_state};
+        let next_sibling = self.generate_next_sibling(&match_view_state);
         let mut expr_match = self.expr_match.clone();
         for (arm, arm_view) in expr_match.arms.iter_mut().zip(self.arm_views.iter()) {
             let arm_code = arm_view.generate_fn_update(
                 &match_view_state,
+                &next_sibling,
                 sub_mod,
                 &self.match_enum_name,
                 parent,
@@ -419,6 +431,7 @@ impl ArmView {
     fn generate_fn_update(
         &self,
         match_view_state: &TokenStream,
+        next_sibling: &TokenStream,
         sub_mod: &SubMod,
         match_enum_name: &Ident,
         parent: &Ident,
@@ -437,7 +450,7 @@ impl ArmView {
             #fn_update_closure_definition
             if !matches!(&#match_view_state.view_state, #match_enum_name::#variant(_)) {
                 #match_view_state.view_state.remove_from(#parent);
-                #match_view_state.view_state = #fn_create_closure_name(#parent, Some(#match_view_state.marker.get_ws_node_ref()));
+                #match_view_state.view_state = #fn_create_closure_name(#parent, #next_sibling);
             }
             if let #match_enum_name::#variant(__spair_match_arm_local_view_state) = &mut #match_view_state.view_state{
                 #fn_update_closure_name(__spair_match_arm_local_view_state, #parent);
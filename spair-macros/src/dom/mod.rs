@@ -1,5 +1,7 @@
 use std::ops::Not;
 
+use child_comp::ChildComp;
+use comp_list::CompList;
 use comp_ref::CompRef;
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
@@ -15,6 +17,8 @@ use view::ViewFnCall;
 
 use crate::MultiErrors;
 
+mod child_comp;
+mod comp_list;
 mod comp_ref;
 mod element;
 mod list;
@@ -44,6 +48,8 @@ pub enum Item {
     List(Box<List>),
     Match(Match),
     CompRef(CompRef),
+    ChildComp(ChildComp),
+    CompList(Box<CompList>),
 }
 
 pub struct SubMod {
@@ -126,6 +132,8 @@ impl Item {
             Item::List(value) => value.first_span(),
             Item::Match(value) => value.first_span(),
             Item::CompRef(value) => value.first_span(),
+            Item::ChildComp(value) => value.first_span(),
+            Item::CompList(value) => value.first_span(),
         }
     }
 
@@ -137,6 +145,8 @@ impl Item {
             Item::List(value) => value.validate_html(errors),
             Item::Match(value) => value.validate_html(errors),
             Item::CompRef(_value) => {}
+            Item::ChildComp(_value) => {}
+            Item::CompList(_value) => {}
         }
     }
 
@@ -150,6 +160,10 @@ impl Item {
                 value.prepare_items_for_generating_code(parent_has_only_one_child)
             }
             Item::CompRef(_value) => {}
+            Item::ChildComp(_value) => {}
+            Item::CompList(value) => {
+                value.prepare_items_for_generating_code(parent_has_only_one_child)
+            }
         }
     }
 
@@ -163,6 +177,8 @@ impl Item {
             Item::List(value) => value.generate_view_state_struct_fields(sub_mod),
             Item::Match(value) => value.generate_view_state_struct_fields(sub_mod),
             Item::CompRef(value) => value.generate_view_state_struct_fields(),
+            Item::ChildComp(value) => value.generate_view_state_struct_fields(),
+            Item::CompList(value) => value.generate_view_state_struct_fields(),
         }
     }
 
@@ -174,6 +190,8 @@ impl Item {
             Item::List(value) => value.generate_view_states_for_matches_and_lists(),
             Item::Match(value) => value.generate_view_states_for_matches_and_lists(),
             Item::CompRef(_value) => quote! {},
+            Item::ChildComp(_value) => quote! {},
+            Item::CompList(_value) => quote! {},
         }
     }
 
@@ -185,6 +203,8 @@ impl Item {
             Item::List(value) => value.generate_html_string(html_string),
             Item::Match(value) => value.generate_html_string(html_string),
             Item::CompRef(value) => value.generate_html_string(html_string),
+            Item::ChildComp(value) => value.generate_html_string(html_string),
+            Item::CompList(value) => value.generate_html_string(html_string),
         }
     }
 
@@ -196,6 +216,8 @@ impl Item {
             Item::List(value) => value.generate_fn_create(sub_mod, last_node),
             Item::Match(value) => value.generate_fn_create(sub_mod, last_node),
             Item::CompRef(value) => value.generate_fn_create(last_node),
+            Item::ChildComp(value) => value.generate_fn_create(last_node),
+            Item::CompList(value) => value.generate_fn_create(last_node),
         }
     }
 
@@ -207,6 +229,8 @@ impl Item {
             Item::List(value) => value.spair_indent_to_get_next_node(),
             Item::Match(value) => value.spair_indent_to_get_next_node(),
             Item::CompRef(value) => value.spair_indent_to_get_next_node(),
+            Item::ChildComp(value) => value.spair_indent_to_get_next_node(),
+            Item::CompList(value) => value.spair_indent_to_get_next_node(),
         }
     }
 
@@ -218,6 +242,8 @@ impl Item {
             Item::List(value) => value.generate_return_value(),
             Item::Match(value) => value.generate_fn_create_return_value(),
             Item::CompRef(value) => value.generate_fn_create_return_value(),
+            Item::ChildComp(value) => value.generate_fn_create_return_value(),
+            Item::CompList(value) => value.generate_fn_create_return_value(),
         }
     }
 
@@ -234,6 +260,8 @@ impl Item {
             Item::List(value) => value.generate_fn_update(sub_mod, view_state, parent),
             Item::Match(value) => value.generate_fn_update(sub_mod, view_state, parent),
             Item::CompRef(value) => value.generate_fn_update(view_state, parent),
+            Item::ChildComp(value) => value.generate_fn_update(view_state, parent),
+            Item::CompList(value) => value.generate_fn_update(view_state),
         }
     }
 
@@ -247,6 +275,10 @@ impl Item {
             }
             Item::Match(value) => value.generate_fn_remove_from(parent),
             Item::CompRef(value) => value.generate_fn_remove_from(parent),
+            Item::ChildComp(value) => value.generate_fn_remove_from(parent),
+            Item::CompList(_value) => {
+                panic!("List must not be at the root of a view");
+            }
         }
     }
 }
@@ -285,6 +317,9 @@ impl Items {
         errors: &mut MultiErrors,
     ) {
         match expr {
+            Expr::Call(expr_call) if is_child_comp_call(&expr_call) => {
+                self.collect_child_comp(expr_call, stage_picker, item_counter, errors);
+            }
             Expr::Call(expr_call) => {
                 self.collect_from_expr_call(at_root, expr_call, stage_picker, item_counter, errors);
             }
@@ -361,7 +396,7 @@ impl Items {
         } = expr_call;
         match expr_as_ident(
             *func,
-            "Expected HTML tags (div, input...), ViewName, or spair_list",
+            "Expected HTML tags (div, input...), ViewName, spair_list or spair_comp_list",
         ) {
             Ok(ident) => {
                 if ident == "text" {
@@ -376,6 +411,18 @@ impl Items {
                         item_counter,
                         errors,
                     );
+                } else if ident == "spair_comp_list" {
+                    if let Some(comp_list) = CompList::new(
+                        at_root,
+                        ident,
+                        paren_token,
+                        args,
+                        stage_picker,
+                        item_counter,
+                        errors,
+                    ) {
+                        self.items.push(Item::CompList(Box::new(comp_list)));
+                    }
                 } else if ident == "spair_comp_ref" {
                     if args.len() != 1 {
                         errors.error_at(args.span(), "Expected exactly 1 arg");
@@ -417,6 +464,29 @@ impl Items {
         }
     }
 
+    fn collect_child_comp(
+        &mut self,
+        expr_call: ExprCall,
+        stage_picker: &StagePicker,
+        item_counter: &mut ItemCounter,
+        errors: &mut MultiErrors,
+    ) {
+        let Expr::Path(expr_path) = *expr_call.func else {
+            return;
+        };
+        let comp_name = child_comp_path(&expr_path.path);
+        if expr_call.args.len() != 1 {
+            errors.error_at(
+                expr_call.paren_token.span.join(),
+                "Expected exactly 1 arg for the props",
+            );
+            return;
+        }
+        let props = expr_call.args.into_pairs().next().unwrap().into_value();
+        let child_comp = ChildComp::new(comp_name, props, stage_picker, item_counter);
+        self.items.push(Item::ChildComp(child_comp));
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn collect_list(
         &mut self,
@@ -593,6 +663,27 @@ impl Items {
     }
 }
 
+// `ChildName::props(...)` or `module::ChildName::props(...)`
+fn is_child_comp_call(expr_call: &ExprCall) -> bool {
+    let Expr::Path(expr_path) = expr_call.func.as_ref() else {
+        return false;
+    };
+    let segments = &expr_path.path.segments;
+    let len = segments.len();
+    expr_path.qself.is_none()
+        && len >= 2
+        && segments[len - 1].ident == "props"
+        && is_first_letter_uppercase(&segments[len - 2].ident.to_string())
+}
+
+// The path to the component in `path::to::ChildName::props`
+fn child_comp_path(path: &syn::Path) -> syn::Path {
+    let mut comp_path = path.clone();
+    comp_path.segments.pop();
+    comp_path.segments.pop_punct();
+    comp_path
+}
+
 fn is_first_letter_uppercase(name: &str) -> bool {
     name.chars()
         .next()
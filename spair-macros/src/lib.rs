@@ -2,10 +2,13 @@ use std::ops::Not;
 
 use proc_macro::TokenStream;
 use proc_macro2::Span;
-use syn::{ItemImpl, Result, parse_macro_input};
+use syn::{DeriveInput, ItemImpl, Result, parse_macro_input};
 
 mod component;
 mod dom;
+mod query;
+mod queue_render;
+mod route;
 mod view;
 
 #[proc_macro_attribute]
@@ -40,6 +43,28 @@ pub fn create_view(args: TokenStream, input: TokenStream) -> TokenStream {
     }
 }
 
+#[proc_macro_derive(Route, attributes(route, not_found, query, fragment))]
+pub fn derive_route(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input);
+
+    match route::RouteEnum::from_derive_input(input) {
+        Ok(route) => route.generate(),
+        Err(error) => error.to_compile_error(),
+    }
+    .into()
+}
+
+#[proc_macro_derive(Query)]
+pub fn derive_query(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input);
+
+    match query::QueryStruct::from_derive_input(input) {
+        Ok(query) => query.generate(),
+        Err(error) => error.to_compile_error(),
+    }
+    .into()
+}
+
 #[derive(Default)]
 struct MultiErrors {
     error: Option<syn::Error>,
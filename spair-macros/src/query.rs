@@ -0,0 +1,118 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Ident, Result, Type};
+
+/// `#[derive(Query)]` for a struct with named fields. The key of a field is its name.
+pub struct QueryStruct {
+    name: Ident,
+    fields: Vec<QueryField>,
+}
+
+struct QueryField {
+    name: Ident,
+    kind: FieldKind,
+}
+
+enum FieldKind {
+    Required,
+    Optional,
+    Repeated,
+}
+
+impl QueryStruct {
+    pub fn from_derive_input(input: DeriveInput) -> Result<Self> {
+        let fields = match input.data {
+            Data::Struct(data_struct) => match data_struct.fields {
+                Fields::Named(fields) => fields.named,
+                Fields::Unit => Default::default(),
+                Fields::Unnamed(_) => {
+                    return Err(syn::Error::new(
+                        input.ident.span(),
+                        "`Query` can only be derived for structs with named fields",
+                    ));
+                }
+            },
+            _ => {
+                return Err(syn::Error::new(
+                    input.ident.span(),
+                    "`Query` can only be derived for structs",
+                ));
+            }
+        };
+        let fields = fields
+            .into_iter()
+            .filter_map(|field| {
+                Some(QueryField {
+                    kind: FieldKind::of(&field.ty),
+                    name: field.ident?,
+                })
+            })
+            .collect();
+        Ok(QueryStruct {
+            name: input.ident,
+            fields,
+        })
+    }
+
+    pub fn generate(&self) -> TokenStream {
+        let name = &self.name;
+        let query = Ident::new("__spair_query", proc_macro2::Span::call_site());
+        let from_query_fields = self.fields.iter().map(|field| {
+            let field_name = &field.name;
+            let key = field_name.to_string();
+            let method = match field.kind {
+                FieldKind::Required => quote! {required},
+                FieldKind::Optional => quote! {optional},
+                FieldKind::Repeated => quote! {all},
+            };
+            quote! { #field_name: #query.#method(#key)?, }
+        });
+        let to_query_fields = self.fields.iter().map(|field| {
+            let field_name = &field.name;
+            let key = field_name.to_string();
+            match field.kind {
+                FieldKind::Required => quote! { #query.push(#key, &self.#field_name); },
+                FieldKind::Optional => quote! {
+                    if let ::std::option::Option::Some(value) = &self.#field_name {
+                        #query.push(#key, value);
+                    }
+                },
+                FieldKind::Repeated => quote! {
+                    for value in &self.#field_name {
+                        #query.push(#key, value);
+                    }
+                },
+            }
+        });
+        quote! {
+            impl ::spair::FromQuery for #name {
+                fn from_query(
+                    #query: &::spair::Query,
+                ) -> ::std::result::Result<Self, ::spair::QueryError> {
+                    ::std::result::Result::Ok(Self {
+                        #(#from_query_fields)*
+                    })
+                }
+            }
+
+            impl ::spair::ToQuery for #name {
+                fn to_query(&self, #query: &mut ::spair::Query) {
+                    #(#to_query_fields)*
+                }
+            }
+        }
+    }
+}
+
+impl FieldKind {
+    fn of(ty: &Type) -> Self {
+        let Type::Path(type_path) = ty else {
+            return FieldKind::Required;
+        };
+        match type_path.path.segments.last() {
+            Some(segment) if segment.ident == "Option" => FieldKind::Optional,
+            Some(segment) if segment.ident == "Vec" => FieldKind::Repeated,
+            _ => FieldKind::Required,
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use std::ops::Not;
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::quote;
+use syn::{
+    Attribute, FnArg, Ident, ImplItemFn, Local, Stmt, Type, punctuated::Punctuated,
+    spanned::Spanned, token::Comma,
+};
+
+use crate::{MultiErrors, view::collect_variable_names_from_pat};
+
+const QUEUED: &str = "queued";
+
+/// Fields listed in `#[queued(...)]` on `fn update` of a component. Each piece of update
+/// code is checked for the fields that it reads, so that `fn update_queued` only executes
+/// the pieces that read the changed fields.
+pub struct QueueRender {
+    fields: Vec<Ident>,
+    context: Ident,
+    local_variables: Vec<(Ident, Vec<bool>)>,
+}
+
+enum Read {
+    Field(usize),
+    Other,
+    Everything,
+}
+
+impl QueueRender {
+    /// Remove `#[queued(...)]` from `fn update` and collect the listed fields.
+    pub fn take_from_fn_update(
+        fn_update: &mut ImplItemFn,
+        errors: &mut MultiErrors,
+    ) -> Option<QueueRender> {
+        let index = fn_update
+            .attrs
+            .iter()
+            .position(|attr| attr.path().is_ident(QUEUED))?;
+        let attr = fn_update.attrs.remove(index);
+        let fields = parse_fields(&attr, errors)?;
+
+        let Some(context) = find_context_arg(fn_update) else {
+            errors.error_at(
+                fn_update.sig.span(),
+                "`#[queued(...)]` requires an argument of type `&Context<Self>` in `fn update`",
+            );
+            return None;
+        };
+
+        let mut queue_render = QueueRender {
+            fields,
+            context,
+            local_variables: Vec::new(),
+        };
+        for stmt in fn_update.block.stmts.iter() {
+            if let Stmt::Local(Local { pat, init, .. }) = stmt {
+                let mut variables = Vec::new();
+                collect_variable_names_from_pat(pat, &mut variables);
+                let reads = match init {
+                    Some(init) => {
+                        let expr = &init.expr;
+                        queue_render.reads(quote! {#expr})
+                    }
+                    None => vec![false; queue_render.fields.len()],
+                };
+                for variable in variables {
+                    queue_render.local_variables.push((variable, reads.clone()));
+                }
+            }
+        }
+        Some(queue_render)
+    }
+
+    /// Generate `fn update_queued` from the user's `fn update` and pieces of update code.
+    pub fn generate_fn_update_queued(
+        &self,
+        fn_update: &ImplItemFn,
+        view_state: &Ident,
+        view_state_name: &Ident,
+        update_snippets: Vec<TokenStream>,
+    ) -> TokenStream {
+        let inputs = &fn_update.sig.inputs;
+        let stmts = &fn_update.block.stmts;
+        let context = &self.context;
+        let changed_flags: Vec<Ident> = self
+            .fields
+            .iter()
+            .map(|field| {
+                Ident::new(
+                    &format!("_spair_queued_{field}_changed_"),
+                    Span::call_site(),
+                )
+            })
+            .collect();
+        let take_changed_flags: TokenStream = self
+            .fields
+            .iter()
+            .zip(changed_flags.iter())
+            .map(|(field, flag)| quote! {let #flag = #context.state.#field.take_changed();})
+            .collect();
+        let updates: TokenStream = update_snippets
+            .into_iter()
+            .filter(|snippet| snippet.is_empty().not())
+            .filter_map(|snippet| {
+                let reads = self.reads(snippet.clone());
+                let flags: Vec<&Ident> = changed_flags
+                    .iter()
+                    .zip(reads.iter())
+                    .filter_map(|(flag, read)| read.then_some(flag))
+                    .collect();
+                if flags.is_empty() {
+                    return None;
+                }
+                Some(quote! {
+                    if #(#flags)||* {
+                        #snippet
+                    }
+                })
+            })
+            .collect();
+        quote! {
+            #[allow(unused)]
+            fn update_queued(#view_state: &mut #view_state_name, #inputs) {
+                use ::spair::{RenderOptionWithDefault, WsNodeFns};
+                #(#stmts)*
+                #take_changed_flags
+                #updates
+            }
+        }
+    }
+
+    /// Code to put at the end of `fn update`: a full update renders all `Queued` fields.
+    pub fn generate_marking_rendered(&self) -> TokenStream {
+        let context = &self.context;
+        self.fields
+            .iter()
+            .map(|field| quote! {#context.state.#field.mark_rendered();})
+            .collect()
+    }
+
+    fn reads(&self, tokens: TokenStream) -> Vec<bool> {
+        let mut reads = vec![false; self.fields.len()];
+        self.collect_reads(tokens, &mut reads);
+        reads
+    }
+
+    fn collect_reads(&self, tokens: TokenStream, reads: &mut [bool]) {
+        let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+        for (index, token) in tokens.iter().enumerate() {
+            match token {
+                TokenTree::Group(group) => self.collect_reads(group.stream(), reads),
+                TokenTree::Ident(ident) => {
+                    // `something.ident` is a field or a method, not a variable
+                    if index > 0 && is_punct(&tokens[index - 1], '.') {
+                        continue;
+                    }
+                    if *ident == self.context {
+                        match self.read_from_context(&tokens[index + 1..]) {
+                            Read::Field(field_index) => reads[field_index] = true,
+                            Read::Other => {}
+                            Read::Everything => reads.iter_mut().for_each(|read| *read = true),
+                        }
+                    } else if let Some((_, local_reads)) = self
+                        .local_variables
+                        .iter()
+                        .rev()
+                        .find(|(variable, _)| variable == ident)
+                    {
+                        for (read, local_read) in reads.iter_mut().zip(local_reads.iter()) {
+                            *read |= *local_read;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // `tokens` are the tokens that follow the context variable
+    fn read_from_context(&self, tokens: &[TokenTree]) -> Read {
+        let ident_at = |index: usize| match tokens.get(index) {
+            Some(TokenTree::Ident(ident)) => Some(ident),
+            _ => None,
+        };
+        let punct_at =
+            |index: usize, c: char| tokens.get(index).map(|v| is_punct(v, c)).unwrap_or(false);
+
+        if punct_at(0, '.').not() {
+            return Read::Everything;
+        }
+        match ident_at(1) {
+            Some(ident) if ident == "comp" => return Read::Other,
+            Some(ident) if ident == "state" => {}
+            _ => return Read::Everything,
+        }
+        if punct_at(2, '.').not() {
+            return Read::Everything;
+        }
+        let Some(field) = ident_at(3) else {
+            return Read::Everything;
+        };
+        let is_method_call = matches!(
+            tokens.get(4),
+            Some(TokenTree::Group(group)) if group.delimiter() == proc_macro2::Delimiter::Parenthesis
+        ) || punct_at(4, ':');
+        if is_method_call {
+            return Read::Everything;
+        }
+        match self.fields.iter().position(|v| v == field) {
+            Some(field_index) => Read::Field(field_index),
+            None => Read::Other,
+        }
+    }
+}
+
+fn is_punct(token: &TokenTree, c: char) -> bool {
+    matches!(token, TokenTree::Punct(punct) if punct.as_char() == c)
+}
+
+fn parse_fields(attr: &Attribute, errors: &mut MultiErrors) -> Option<Vec<Ident>> {
+    match attr.parse_args_with(Punctuated::<Ident, Comma>::parse_terminated) {
+        Ok(fields) if fields.is_empty() => {
+            errors.error_at(attr.span(), "Expected at least one field name");
+            None
+        }
+        Ok(fields) => Some(fields.into_iter().collect()),
+        Err(error) => {
+            errors.combine(error);
+            None
+        }
+    }
+}
+
+fn find_context_arg(fn_update: &ImplItemFn) -> Option<Ident> {
+    fn_update.sig.inputs.iter().find_map(|input| {
+        let FnArg::Typed(pat_type) = input else {
+            return None;
+        };
+        let syn::Pat::Ident(pat_ident) = &*pat_type.pat else {
+            return None;
+        };
+        let Type::Reference(type_reference) = &*pat_type.ty else {
+            return None;
+        };
+        let Type::Path(type_path) = &*type_reference.elem else {
+            return None;
+        };
+        type_path
+            .path
+            .segments
+            .last()
+            .filter(|segment| segment.ident == "Context")
+            .map(|_| pat_ident.ident.clone())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse_quote;
+
+    use super::QueueRender;
+    use crate::MultiErrors;
+
+    fn queue_render() -> QueueRender {
+        let mut fn_update = parse_quote! {
+            #[queued(count, items)]
+            fn update(ustate: &Self, ucontext: &Context<Self>) {
+                let total = ucontext.state.count + 1;
+                let title = &ucontext.state.title;
+            }
+        };
+        let mut errors = MultiErrors::default();
+        let queue_render = QueueRender::take_from_fn_update(&mut fn_update, &mut errors);
+        assert!(errors.error.is_none());
+        queue_render.unwrap()
+    }
+
+    #[test]
+    fn direct_reads() {
+        let queue_render = queue_render();
+        assert_eq!(
+            queue_render.reads(quote! {ucontext.state.count}),
+            [true, false]
+        );
+        assert_eq!(
+            queue_render.reads(quote! {(ucontext.state.items, ucontext.state.count)}),
+            [true, true]
+        );
+    }
+
+    #[test]
+    fn reads_through_a_deref() {
+        let queue_render = queue_render();
+        assert_eq!(
+            queue_render.reads(quote! {*ucontext.state.count}),
+            [true, false]
+        );
+        assert_eq!(
+            queue_render.reads(quote! {&*ucontext.state.items}),
+            [false, true]
+        );
+    }
+
+    #[test]
+    fn reads_nested_in_method_calls() {
+        let queue_render = queue_render();
+        assert_eq!(
+            queue_render.reads(quote! {format!("{}", ucontext.state.items.len())}),
+            [false, true]
+        );
+        assert_eq!(
+            queue_render.reads(quote! {value.max(*ucontext.state.count)}),
+            [true, false]
+        );
+        // A method of the state may read any field
+        assert_eq!(
+            queue_render.reads(quote! {ucontext.state.visible_items()}),
+            [true, true]
+        );
+    }
+
+    #[test]
+    fn fields_not_listed() {
+        let queue_render = queue_render();
+        assert_eq!(
+            queue_render.reads(quote! {ucontext.state.title.len()}),
+            [false, false]
+        );
+        assert_eq!(
+            queue_render.reads(quote! {ucontext.comp.callback(|state| state.count += 1)}),
+            [false, false]
+        );
+    }
+
+    #[test]
+    fn reads_through_local_variables() {
+        let queue_render = queue_render();
+        assert_eq!(queue_render.reads(quote! {total * 2}), [true, false]);
+        assert_eq!(queue_render.reads(quote! {title}), [false, false]);
+    }
+}
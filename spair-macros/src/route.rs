@@ -0,0 +1,752 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Attribute, Data, DeriveInput, Fields, Ident, LitStr, Result, Type, spanned::Spanned};
+
+use crate::MultiErrors;
+
+/// `#[derive(Route)]` for an enum:
+/// ```ignore
+/// #[derive(spair::Route)]
+/// enum AppRoute {
+///     #[route("/")]
+///     Home,
+///     #[route("/users/:id/posts/:post_id")]
+///     Post { id: u32, post_id: u32 },
+///     #[route("#/settings/:tab")]
+///     Settings(String),
+///     // The rest of the path is parsed by `AdminRoute` which implements `spair::NestedRoute`
+///     #[route("/admin/*rest")]
+///     Admin(AdminRoute),
+///     // `SearchQuery` and `Anchor` implement `spair::FromQuery` and `spair::ToQuery`
+///     #[route("/search")]
+///     Search {
+///         #[query]
+///         query: SearchQuery,
+///         #[fragment]
+///         anchor: Anchor,
+///     },
+///     #[not_found]
+///     NotFound,
+/// }
+/// ```
+pub struct RouteEnum {
+    name: Ident,
+    variants: Vec<RouteVariant>,
+    not_found: Ident,
+}
+
+struct RouteVariant {
+    name: Ident,
+    pattern: Option<Pattern>,
+    kind: VariantKind,
+    fields: Vec<RouteField>,
+}
+
+#[derive(PartialEq)]
+enum VariantKind {
+    Unit,
+    Named,
+    Unnamed,
+}
+
+struct RouteField {
+    name: Option<Ident>,
+    ty: Type,
+    source: FieldSource,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FieldSource {
+    /// A `:param` or `*rest` of the pattern
+    Path,
+    /// `#[query]`, parsed from the query string
+    Query,
+    /// `#[fragment]`, parsed from the fragment in the query string format
+    Fragment,
+}
+
+struct Pattern {
+    text: String,
+    span: Span,
+    in_hash: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(PartialEq)]
+enum Segment {
+    Literal(String),
+    Param(Ident),
+    /// `*rest`, the remaining segments for a nested route
+    Rest(Ident),
+}
+
+/// Local variables of the generated `from_url`/`from_path` that a pattern is matched with
+struct MatchSource {
+    segments: Ident,
+    search: Ident,
+    query: Ident,
+    fragment: Ident,
+}
+
+impl MatchSource {
+    fn new(prefix: &str) -> Self {
+        MatchSource {
+            segments: format_ident!("__spair_{prefix}_segments"),
+            search: format_ident!("__spair_{prefix}_search"),
+            query: format_ident!("__spair_{prefix}_query"),
+            fragment: format_ident!("__spair_fragment"),
+        }
+    }
+}
+
+impl RouteEnum {
+    pub fn from_derive_input(input: DeriveInput) -> Result<Self> {
+        let mut errors = MultiErrors::default();
+        let Data::Enum(data_enum) = input.data else {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "`Route` can only be derived for enums",
+            ));
+        };
+
+        let mut variants = Vec::new();
+        let mut not_found: Option<Ident> = None;
+        for variant in data_enum.variants {
+            let route_attr = find_attr(&variant.attrs, "route");
+            let not_found_attr = find_attr(&variant.attrs, "not_found");
+            if route_attr.is_none() && not_found_attr.is_none() {
+                errors.error_at(
+                    variant.ident.span(),
+                    "Expected `#[route(\"/path/:param\")]` or `#[not_found]` on this variant",
+                );
+                continue;
+            }
+            if let Some(attr) = not_found_attr {
+                if !matches!(variant.fields, Fields::Unit) {
+                    errors.error_at(
+                        variant.ident.span(),
+                        "A `#[not_found]` variant must be a unit variant",
+                    );
+                }
+                if not_found.is_some() {
+                    errors.error_at(attr.span(), "Only one variant can be `#[not_found]`");
+                }
+                not_found = Some(variant.ident.clone());
+            }
+
+            let kind = match &variant.fields {
+                Fields::Unit => VariantKind::Unit,
+                Fields::Named(_) => VariantKind::Named,
+                Fields::Unnamed(_) => VariantKind::Unnamed,
+            };
+            let fields = variant
+                .fields
+                .into_iter()
+                .map(|field| RouteField {
+                    source: FieldSource::from_attrs(&field.attrs, &mut errors),
+                    name: field.ident,
+                    ty: field.ty,
+                })
+                .collect();
+            let pattern = match route_attr.map(|attr| attr.parse_args::<LitStr>()) {
+                Some(Ok(lit)) => Pattern::parse(&lit, &mut errors),
+                Some(Err(e)) => {
+                    errors.combine(e);
+                    None
+                }
+                None => None,
+            };
+            let route_variant = RouteVariant {
+                name: variant.ident,
+                pattern,
+                kind,
+                fields,
+            };
+            route_variant.validate_fields(&mut errors);
+            variants.push(route_variant);
+        }
+        check_unreachable_patterns(&variants, &mut errors);
+
+        let Some(not_found) = not_found else {
+            return errors.with_last_error_at(
+                input.ident.span(),
+                "Expected a `#[not_found]` variant for a location that matches no route",
+            );
+        };
+        errors.report_error()?;
+        Ok(RouteEnum {
+            name: input.ident,
+            variants,
+            not_found,
+        })
+    }
+
+    pub fn generate(&self) -> TokenStream {
+        let name = &self.name;
+        let not_found = &self.not_found;
+        let path = MatchSource::new("path");
+        let hash = MatchSource::new("hash");
+        let match_arms = self.variants.iter().filter_map(|variant| {
+            let source = match variant.pattern.as_ref()?.in_hash {
+                true => &hash,
+                false => &path,
+            };
+            Some(variant.generate_matching(source))
+        });
+        let nested_match_arms = self
+            .variants
+            .iter()
+            .map(|variant| variant.generate_matching(&path));
+        let url_arms = self
+            .variants
+            .iter()
+            .map(|variant| variant.generate_url(true));
+        let path_arms = self
+            .variants
+            .iter()
+            .map(|variant| variant.generate_url(false));
+
+        let MatchSource {
+            segments: path_segments,
+            search: path_search,
+            query: path_query,
+            fragment,
+        } = &path;
+        let MatchSource {
+            segments: hash_segments,
+            search: hash_search,
+            query: hash_query,
+            ..
+        } = &hash;
+        quote! {
+            impl ::spair::Route for #name {
+                fn from_location(location: &::spair::web_sys::Location) -> Self {
+                    <Self as ::spair::RouteFromUrl>::from_url(&location.href().unwrap_or_default())
+                }
+
+                fn url(&self) -> ::std::string::String {
+                    match self {
+                        #(#url_arms)*
+                    }
+                }
+            }
+
+            impl ::spair::RouteFromUrl for #name {
+                fn from_url(url: &str) -> Self {
+                    let (path, #path_search, hash) = ::spair::url_parts(url);
+                    let #path_segments = ::spair::route_segments(&path);
+                    let #path_query = ::spair::Query::parse(&#path_search);
+                    let #fragment = ::spair::Query::parse(&hash);
+                    let (hash_path, #hash_search, _) =
+                        ::spair::split_route_path(hash.strip_prefix('#').unwrap_or(&hash));
+                    let #hash_segments = ::spair::route_segments(hash_path);
+                    let #hash_query = ::spair::Query::parse(#hash_search);
+                    #(#match_arms)*
+                    Self::#not_found
+                }
+            }
+
+            impl ::spair::NestedRoute for #name {
+                fn from_path(path: &str) -> Self {
+                    let (path, #path_search, hash) = ::spair::split_route_path(path);
+                    let #path_segments = ::spair::route_segments(path);
+                    let #path_query = ::spair::Query::parse(#path_search);
+                    let #fragment = ::spair::Query::parse(hash);
+                    #(#nested_match_arms)*
+                    Self::#not_found
+                }
+
+                fn path(&self) -> ::std::string::String {
+                    match self {
+                        #(#path_arms)*
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn pattern_params(segments: &[Segment]) -> impl Iterator<Item = &Ident> {
+    segments.iter().filter_map(|segment| match segment {
+        Segment::Param(param) | Segment::Rest(param) => Some(param),
+        Segment::Literal(_) => None,
+    })
+}
+
+fn find_attr<'a>(attrs: &'a [Attribute], name: &str) -> Option<&'a Attribute> {
+    attrs.iter().find(|attr| attr.path().is_ident(name))
+}
+
+impl FieldSource {
+    fn from_attrs(attrs: &[Attribute], errors: &mut MultiErrors) -> Self {
+        match (find_attr(attrs, "query"), find_attr(attrs, "fragment")) {
+            (None, None) => FieldSource::Path,
+            (Some(_), None) => FieldSource::Query,
+            (None, Some(_)) => FieldSource::Fragment,
+            (Some(_), Some(attr)) => {
+                errors.error_at(
+                    attr.span(),
+                    "A field can not be both `#[query]` and `#[fragment]`",
+                );
+                FieldSource::Query
+            }
+        }
+    }
+}
+
+impl Pattern {
+    fn parse(lit: &LitStr, errors: &mut MultiErrors) -> Option<Self> {
+        let text = lit.value();
+        let span = lit.span();
+        let (in_hash, path) = if let Some(path) = text.strip_prefix("#/") {
+            (true, path)
+        } else if let Some(path) = text.strip_prefix('/') {
+            (false, path)
+        } else {
+            errors.error_at(span, "A route pattern must start with `/` or `#/`");
+            return None;
+        };
+        if path.contains(['?', '#']) {
+            errors.error_at(
+                span,
+                "Use a `#[query]` or `#[fragment]` field instead of `?` or `#` in a route pattern",
+            );
+            return None;
+        }
+
+        let mut segments = Vec::new();
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            if matches!(segments.last(), Some(Segment::Rest(_))) {
+                errors.error_at(span, "`*rest` must be the last segment of a route pattern");
+                return None;
+            }
+            if let Some(param) = segment.strip_prefix(':').or(segment.strip_prefix('*')) {
+                let Ok(param) = syn::parse_str::<Ident>(param) else {
+                    errors.error_at(span, &format!("`{param}` is not a valid parameter name"));
+                    return None;
+                };
+                if pattern_params(&segments).any(|p| *p == param) {
+                    errors.error_at(span, &format!("Duplicate parameter `{param}`"));
+                    return None;
+                }
+                match segment.starts_with('*') {
+                    true => segments.push(Segment::Rest(param)),
+                    false => segments.push(Segment::Param(param)),
+                }
+            } else if segment.contains([':', '*']) {
+                errors.error_at(
+                    span,
+                    &format!("`:` and `*` are only allowed at the start of a segment: `{segment}`"),
+                );
+                return None;
+            } else {
+                segments.push(Segment::Literal(segment.to_string()));
+            }
+        }
+        Some(Pattern {
+            text,
+            span,
+            in_hash,
+            segments,
+        })
+    }
+
+    fn params(&self) -> impl Iterator<Item = &Ident> {
+        pattern_params(&self.segments)
+    }
+
+    fn rest(&self) -> Option<&Ident> {
+        match self.segments.last() {
+            Some(Segment::Rest(rest)) => Some(rest),
+            _ => None,
+        }
+    }
+
+    /// Segments before `*rest`
+    fn fixed_segments(&self) -> &[Segment] {
+        match self.rest() {
+            Some(_) => &self.segments[..self.segments.len() - 1],
+            None => &self.segments,
+        }
+    }
+
+    /// The format string for the segments before `*rest`
+    fn format_string(&self, with_hash: bool) -> String {
+        let mut format_string = match self.in_hash && with_hash {
+            true => "#/".to_string(),
+            false => "/".to_string(),
+        };
+        let segments: Vec<String> = self
+            .fixed_segments()
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(literal) => literal.replace('{', "{{").replace('}', "}}"),
+                Segment::Param(_) | Segment::Rest(_) => "{}".to_string(),
+            })
+            .collect();
+        format_string.push_str(&segments.join("/"));
+        format_string
+    }
+}
+
+impl RouteVariant {
+    fn path_fields(&self) -> impl Iterator<Item = &RouteField> {
+        self.fields
+            .iter()
+            .filter(|field| field.source == FieldSource::Path)
+    }
+
+    fn has_fields_from(&self, source: FieldSource) -> bool {
+        self.fields.iter().any(|field| field.source == source)
+    }
+
+    fn validate_fields(&self, errors: &mut MultiErrors) {
+        let Some(pattern) = self.pattern.as_ref() else {
+            return;
+        };
+        if pattern.in_hash && self.has_fields_from(FieldSource::Fragment) {
+            errors.error_at(
+                pattern.span,
+                "A route that is matched with the hash can not have a `#[fragment]` field",
+            );
+        }
+        let params: Vec<&Ident> = pattern.params().collect();
+        match self.kind {
+            VariantKind::Unit if !params.is_empty() => errors.error_at(
+                pattern.span,
+                &format!(
+                    "The pattern has parameters, but `{}` has no fields",
+                    self.name
+                ),
+            ),
+            VariantKind::Unit => {}
+            VariantKind::Named => {
+                for param in params.iter() {
+                    if !self
+                        .path_fields()
+                        .any(|field| field.name.as_ref() == Some(*param))
+                    {
+                        errors.error_at(
+                            pattern.span,
+                            &format!("`{}` has no field named `{param}`", self.name),
+                        );
+                    }
+                }
+                for name in self.path_fields().filter_map(|field| field.name.as_ref()) {
+                    if !params.contains(&name) {
+                        errors.error_at(
+                            name.span(),
+                            &format!(
+                                "Expected a parameter `:{name}` in the route pattern, or `#[query]` or `#[fragment]` on the field"
+                            ),
+                        );
+                    }
+                }
+            }
+            VariantKind::Unnamed => {
+                let count = self.path_fields().count();
+                if count != params.len() {
+                    errors.error_at(
+                        pattern.span,
+                        &format!(
+                            "The pattern has {} parameter(s), but `{}` has {count} field(s) for them",
+                            params.len(),
+                            self.name,
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// The type of each parameter in the order of the pattern
+    fn param_types(&self) -> Vec<Option<&Type>> {
+        let Some(pattern) = self.pattern.as_ref() else {
+            return Vec::new();
+        };
+        pattern
+            .params()
+            .enumerate()
+            .map(|(index, param)| {
+                let field = match self.kind {
+                    VariantKind::Unit => None,
+                    VariantKind::Named => self
+                        .path_fields()
+                        .find(|field| field.name.as_ref() == Some(param)),
+                    VariantKind::Unnamed => self.path_fields().nth(index),
+                };
+                field.map(|field| &field.ty)
+            })
+            .collect()
+    }
+
+    /// The local variable for each field
+    fn field_values(&self) -> Vec<Ident> {
+        let mut params = self.pattern.iter().flat_map(Pattern::params);
+        self.fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let param = match (field.source, self.kind == VariantKind::Named) {
+                    (FieldSource::Path, true) => field.name.as_ref(),
+                    (FieldSource::Path, false) => params.next(),
+                    _ => None,
+                };
+                match param {
+                    Some(param) => format_ident!("__spair_param_{param}"),
+                    None => format_ident!("__spair_field_{index}"),
+                }
+            })
+            .collect()
+    }
+
+    fn constructor(&self, values: &[Ident]) -> TokenStream {
+        let name = &self.name;
+        match self.kind {
+            VariantKind::Unit => quote! { Self::#name },
+            VariantKind::Named => {
+                let names = self.fields.iter().map(|field| &field.name);
+                quote! { Self::#name { #(#names: #values),* } }
+            }
+            VariantKind::Unnamed => quote! { Self::#name ( #(#values),* ) },
+        }
+    }
+
+    fn generate_matching(&self, source: &MatchSource) -> TokenStream {
+        let Some(pattern) = self.pattern.as_ref() else {
+            return quote! {};
+        };
+        let MatchSource {
+            segments,
+            search,
+            query,
+            fragment,
+        } = source;
+        let param_values: Vec<Ident> = pattern
+            .params()
+            .map(|param| format_ident!("__spair_param_{param}"))
+            .collect();
+        let mut value_iter = param_values.iter();
+        let slice_items = pattern.segments.iter().map(|segment| match segment {
+            Segment::Literal(literal) => quote! { #literal },
+            Segment::Param(_) => {
+                let value = value_iter.next();
+                quote! { #value }
+            }
+            Segment::Rest(_) => {
+                let value = value_iter.next();
+                quote! { #value @ .. }
+            }
+        });
+        let types = self.param_types();
+        let parsed_params = param_values.iter().zip(types.iter()).enumerate().map(
+            |(index, (value, ty))| {
+                match pattern.rest().is_some() && index == param_values.len() - 1 {
+                    // The query string goes to the nested route, too
+                    true => quote! {
+                        ::std::result::Result::Ok::<_, ()>(<#ty as ::spair::NestedRoute>::from_path(
+                            &::std::format!("{}{}", #value.join("/"), #search)
+                        ))
+                    },
+                    false => quote! { ::spair::percent_decode_path(#value).parse::<#ty>() },
+                }
+            },
+        );
+
+        let values = self.field_values();
+        let (other_values, parsed_others): (Vec<&Ident>, Vec<TokenStream>) = self
+            .fields
+            .iter()
+            .zip(values.iter())
+            .filter_map(|(field, value)| {
+                let from = match field.source {
+                    FieldSource::Path => return None,
+                    FieldSource::Query => query,
+                    FieldSource::Fragment => fragment,
+                };
+                let ty = &field.ty;
+                Some((
+                    value,
+                    quote! { <#ty as ::spair::FromQuery>::from_query(&#from) },
+                ))
+            })
+            .unzip();
+
+        let constructor = self.constructor(&values);
+        if values.is_empty() {
+            return quote! {
+                if let [#(#slice_items),*] = #segments.as_slice() {
+                    return #constructor;
+                }
+            };
+        }
+        // A parameter or a query that fails to parse makes the next patterns to be tried, and
+        // eventually, the `#[not_found]` variant
+        quote! {
+            if let [#(#slice_items),*] = #segments.as_slice() {
+                if let (
+                    #(::std::result::Result::Ok(#param_values),)*
+                    #(::std::result::Result::Ok(#other_values),)*
+                ) =
+                    (#(#parsed_params,)* #(#parsed_others,)*)
+                {
+                    return #constructor;
+                }
+            }
+        }
+    }
+
+    /// `with_hash` is `false` for `NestedRoute::path`, a nested route is always in the part of
+    /// the URL its parent delegates to it.
+    fn generate_url(&self, with_hash: bool) -> TokenStream {
+        let name = &self.name;
+        let Some(pattern) = self.pattern.as_ref() else {
+            // Only the `#[not_found]` variant has no pattern
+            return quote! { Self::#name => ::std::string::String::new(), };
+        };
+        let values = self.field_values();
+        let binding = self.constructor(&values);
+        let param_values: Vec<Ident> = pattern
+            .params()
+            .map(|param| format_ident!("__spair_param_{param}"))
+            .collect();
+        let format_string = pattern.format_string(with_hash);
+        let url = match param_values
+            .split_last()
+            .filter(|_| pattern.rest().is_some())
+        {
+            Some((rest, param_values)) => quote! {
+                ::spair::nested_route_url(
+                    ::std::format!(#format_string, #(::spair::percent_encode(&::std::string::ToString::to_string(#param_values))),*),
+                    &::spair::NestedRoute::path(#rest),
+                )
+            },
+            None => quote! {
+                ::std::format!(#format_string, #(::spair::percent_encode(&::std::string::ToString::to_string(#param_values))),*)
+            },
+        };
+        if !self.has_fields_from(FieldSource::Query) && !self.has_fields_from(FieldSource::Fragment)
+        {
+            return quote! { #binding => #url, };
+        }
+
+        let append = |source: FieldSource| {
+            let fields: Vec<&Ident> = self
+                .fields
+                .iter()
+                .zip(values.iter())
+                .filter(|(field, _)| field.source == source)
+                .map(|(_, value)| value)
+                .collect();
+            if fields.is_empty() {
+                return quote! {};
+            }
+            // A nested route may have added its own query string already
+            let separator = match source {
+                FieldSource::Fragment => quote! { '#' },
+                _ => quote! { if url.contains('?') { '&' } else { '?' } },
+            };
+            quote! {
+                let mut query = ::spair::Query::default();
+                #(::spair::ToQuery::to_query(#fields, &mut query);)*
+                if !query.is_empty() {
+                    url.push(#separator);
+                    url.push_str(&::std::string::ToString::to_string(&query));
+                }
+            }
+        };
+        let append_query = append(FieldSource::Query);
+        let append_fragment = append(FieldSource::Fragment);
+        quote! {
+            #binding => {
+                let mut url = #url;
+                #append_query
+                #append_fragment
+                url
+            }
+        }
+    }
+}
+
+/// A pattern is unreachable if every location that it matches is already matched by an earlier
+/// pattern. A `String` parameter matches any segment, other parameters may fail to parse.
+fn check_unreachable_patterns(variants: &[RouteVariant], errors: &mut MultiErrors) {
+    let patterns: Vec<(&Pattern, Vec<Option<&Type>>, bool)> = variants
+        .iter()
+        .filter_map(|variant| {
+            // Parsing a `#[query]` or `#[fragment]` field may fail, too
+            let may_fail = variant.has_fields_from(FieldSource::Query)
+                || variant.has_fields_from(FieldSource::Fragment);
+            Some((variant.pattern.as_ref()?, variant.param_types(), may_fail))
+        })
+        .collect();
+    for (index, (later, later_types, _)) in patterns.iter().enumerate() {
+        for (earlier, earlier_types, earlier_may_fail) in patterns[..index].iter() {
+            let (earlier_fixed, later_fixed) = (earlier.fixed_segments(), later.fixed_segments());
+            let comparable = match (earlier.rest().is_some(), later.rest().is_some()) {
+                // A `*rest` matches any number of segments, including none
+                (true, _) => later_fixed.len() >= earlier_fixed.len(),
+                (false, true) => false,
+                (false, false) => later_fixed.len() == earlier_fixed.len(),
+            };
+            if earlier.in_hash != later.in_hash || !comparable || *earlier_may_fail {
+                continue;
+            }
+            let mut earlier_types = earlier_types.iter();
+            let mut later_types = later_types.iter();
+            let mut same_shape = earlier.rest().is_some() == later.rest().is_some()
+                && earlier_fixed.len() == later_fixed.len();
+            let covered = earlier_fixed.iter().zip(later_fixed.iter()).all(
+                |(earlier_segment, later_segment)| match (earlier_segment, later_segment) {
+                    (Segment::Literal(a), Segment::Literal(b)) => a == b,
+                    (Segment::Rest(_), _) | (_, Segment::Rest(_)) => {
+                        unreachable!("fixed segments have no `*rest`")
+                    }
+                    (Segment::Literal(_), Segment::Param(_)) => {
+                        later_types.next();
+                        false
+                    }
+                    (Segment::Param(_), Segment::Literal(_)) => {
+                        same_shape = false;
+                        earlier_types.next().is_some_and(|ty| is_string(*ty))
+                    }
+                    (Segment::Param(_), Segment::Param(_)) => {
+                        match (earlier_types.next(), later_types.next()) {
+                            (Some(Some(a)), Some(Some(b))) => {
+                                is_string(Some(*a))
+                                    || quote!(#a).to_string() == quote!(#b).to_string()
+                            }
+                            _ => false,
+                        }
+                    }
+                },
+            );
+            if !covered {
+                continue;
+            }
+            let message = match same_shape {
+                true => format!(
+                    "`{}` conflicts with the route `{}`",
+                    later.text, earlier.text
+                ),
+                false => format!(
+                    "`{}` is unreachable, it is always matched by the route `{}` first",
+                    later.text, earlier.text
+                ),
+            };
+            errors.error_at(later.span, &message);
+            break;
+        }
+    }
+}
+
+fn is_string(ty: Option<&Type>) -> bool {
+    match ty {
+        Some(Type::Path(type_path)) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "String"),
+        _ => false,
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{
+    component::{Comp, Component, RcComp, ShouldRender, create_component},
+    dom::WsElement,
+    helper::DOCUMENT,
+};
+
+/// A component mounted by `spair::mount` or `spair::mount_at_selector`. The component is
+/// unmounted when the handle is dropped.
+#[must_use = "the app is unmounted when its handle is dropped"]
+pub struct AppHandle<C>
+where
+    C: Component + 'static,
+{
+    rc_comp: Option<RcComp<C>>,
+}
+
+/// Create a component and append its root element to `element`. The view of the component
+/// should not use `replace_at_element_id`.
+pub fn mount<C>(element: &web_sys::Element, new_state: impl FnOnce(Comp<C>) -> C) -> AppHandle<C>
+where
+    C: Component + 'static,
+{
+    let rc_comp = create_component(new_state, |_, _: ()| {}, |_, _| {});
+    let parent = WsElement::from(web_sys::Node::from(element.clone()));
+    parent.insert_new_node_before_a_node(&rc_comp.root_element(), None::<&WsElement>);
+    rc_comp.set_mounted_n_call_hook();
+    AppHandle {
+        rc_comp: Some(rc_comp),
+    }
+}
+
+/// Same as `mount`, at the first element that matches `selector`. Return `None` if no element
+/// is found.
+pub fn mount_at_selector<C>(
+    selector: &str,
+    new_state: impl FnOnce(Comp<C>) -> C,
+) -> Option<AppHandle<C>>
+where
+    C: Component + 'static,
+{
+    match DOCUMENT.with(|document| document.query_selector(selector)) {
+        Ok(Some(element)) => Some(mount(&element, new_state)),
+        Ok(None) => {
+            log::error!("Unable to find an element by selector: {selector}");
+            None
+        }
+        Err(e) => {
+            log::error!("Error on querying selector `{selector}`: {e:?}");
+            None
+        }
+    }
+}
+
+impl<C> AppHandle<C>
+where
+    C: Component + 'static,
+{
+    pub fn comp(&self) -> Comp<C> {
+        self.rc_comp
+            .as_ref()
+            .expect("AppHandle always has a component until it is dropped")
+            .comp()
+    }
+
+    /// Execute `f` on the component's state from outside of the app, the same as a callback.
+    pub fn update<S>(&self, f: impl Fn(&mut C) -> S + 'static)
+    where
+        S: Into<ShouldRender>,
+    {
+        self.comp().callback(f).call();
+    }
+
+    /// Remove the component's view and dispose it. The same as dropping the handle.
+    pub fn unmount(self) {}
+
+    /// Keep the app running for the rest of the page's life, like `start_app`.
+    pub fn forget(mut self) {
+        std::mem::forget(self.rc_comp.take());
+    }
+}
+
+impl<C> Drop for AppHandle<C>
+where
+    C: Component + 'static,
+{
+    fn drop(&mut self) {
+        if let Some(rc_comp) = self.rc_comp.take() {
+            rc_comp.unmount();
+        }
+    }
+}
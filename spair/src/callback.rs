@@ -0,0 +1,180 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+
+use wasm_bindgen::{JsCast, closure::Closure};
+
+use crate::{
+    component::{Callback, CallbackArg},
+    helper::WINDOW,
+};
+
+impl<A: 'static> CallbackArg<A> {
+    /// A callback that converts its argument before calling this callback.
+    pub fn map<B: 'static>(&self, f: impl Fn(B) -> A + 'static) -> CallbackArg<B> {
+        let callback = self.clone();
+        CallbackArg::from_fn(move |arg| callback.execute(f(arg)))
+    }
+
+    /// A callback that converts its argument before calling this callback, the call is
+    /// skipped if `f` returns `None`.
+    pub fn filter_map<B: 'static>(&self, f: impl Fn(B) -> Option<A> + 'static) -> CallbackArg<B> {
+        let callback = self.clone();
+        CallbackArg::from_fn(move |arg| {
+            if let Some(arg) = f(arg) {
+                callback.execute(arg);
+            }
+        })
+    }
+
+    /// A callback that only calls this callback with the last argument when it has not been
+    /// called again for `ms` milliseconds. A pending call is still made when the returned
+    /// callback is dropped. Calls are only debounced against each other through the same
+    /// returned callback, so create it once (e.g. for an event listener, in the create stage)
+    /// rather than on every update.
+    pub fn debounce(&self, ms: i32) -> CallbackArg<A> {
+        let debounce = Debounce::new(self.clone(), ms);
+        CallbackArg::from_fn(move |arg| debounce.schedule(arg))
+    }
+
+    /// A callback that calls this callback at most once every `ms` milliseconds, calls in
+    /// between are skipped.
+    pub fn throttle(&self, ms: i32) -> CallbackArg<A> {
+        let callback = self.clone();
+        let last_call = Cell::new(None);
+        CallbackArg::from_fn(move |arg| {
+            let now = js_sys::Date::now();
+            if last_call
+                .get()
+                .is_some_and(|last_call: f64| now - last_call < f64::from(ms))
+            {
+                return;
+            }
+            last_call.set(Some(now));
+            callback.execute(arg);
+        })
+    }
+
+    /// A callback that only calls this callback the first time it is called.
+    pub fn once(&self) -> CallbackArg<A> {
+        let callback = self.clone();
+        let called = Cell::new(false);
+        CallbackArg::from_fn(move |arg| {
+            if !called.replace(true) {
+                callback.execute(arg);
+            }
+        })
+    }
+}
+
+impl Callback {
+    /// See `CallbackArg::debounce`.
+    pub fn debounce(&self, ms: i32) -> Callback {
+        Callback(self.0.debounce(ms))
+    }
+
+    /// See `CallbackArg::throttle`.
+    pub fn throttle(&self, ms: i32) -> Callback {
+        Callback(self.0.throttle(ms))
+    }
+
+    /// See `CallbackArg::once`.
+    pub fn once(&self) -> Callback {
+        Callback(self.0.once())
+    }
+}
+
+struct Debounce<A> {
+    ms: i32,
+    pending_arg: RefCell<Option<A>>,
+    timeout_id: Cell<Option<i32>>,
+    // Holds the debounce itself while a call is pending, so the call is still made if the
+    // debounced callback is dropped, e.g. replaced by a new one in the update stage
+    pending_self: RefCell<Option<Rc<Self>>>,
+    closure: Closure<dyn FnMut()>,
+}
+
+impl<A: 'static> Debounce<A> {
+    fn new(callback: CallbackArg<A>, ms: i32) -> Rc<Self> {
+        Rc::new_cyclic(|debounce: &Weak<Self>| {
+            let debounce = debounce.clone();
+            let closure = Closure::<dyn FnMut()>::new(move || {
+                let Some(debounce) = debounce.upgrade() else {
+                    return;
+                };
+                debounce.timeout_id.set(None);
+                debounce.pending_self.borrow_mut().take();
+                let arg = debounce.pending_arg.take();
+                if let Some(arg) = arg {
+                    callback.call(arg);
+                }
+            });
+            Self {
+                ms,
+                pending_arg: RefCell::new(None),
+                timeout_id: Cell::new(None),
+                pending_self: RefCell::new(None),
+                closure,
+            }
+        })
+    }
+
+    fn schedule(self: &Rc<Self>, arg: A) {
+        *self.pending_arg.borrow_mut() = Some(arg);
+        if let Some(id) = self.timeout_id.take() {
+            WINDOW.with(|window| window.clear_timeout_with_handle(id));
+        }
+        let id = WINDOW.with(|window| {
+            window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                self.closure.as_ref().unchecked_ref(),
+                self.ms,
+            )
+        });
+        match id {
+            Ok(id) => {
+                self.timeout_id.set(Some(id));
+                *self.pending_self.borrow_mut() = Some(Rc::clone(self));
+            }
+            Err(e) => log::error!("Error on setting a timeout for a debounced callback: {e:?}"),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use crate::{component::CallbackArg, helper::WINDOW};
+
+    async fn sleep(ms: i32) {
+        let promise = js_sys::Promise::new(&mut |resolve, _| {
+            WINDOW
+                .with(|window| {
+                    window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                        resolve.unchecked_ref(),
+                        ms,
+                    )
+                })
+                .unwrap();
+        });
+        wasm_bindgen_futures::JsFuture::from(promise).await.unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    async fn debounce_keeps_pending_call_after_drop() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let recorder = calls.clone();
+        let debounced =
+            CallbackArg::from_fn(move |arg: u32| recorder.borrow_mut().push(arg)).debounce(10);
+        debounced.call(1);
+        debounced.call(2);
+        drop(debounced);
+        sleep(50).await;
+        assert_eq!(*calls.borrow(), [2]);
+    }
+}
@@ -0,0 +1,154 @@
+use std::hash::Hash;
+
+use crate::{
+    component::{
+        CallbackArg, Comp, CompNode, Component, RcComp, ShouldRender, execute_then_the_update_queue,
+    },
+    dom::WsElement,
+    keyed_list::{ItemViewState, KeyedList},
+};
+
+/// A component that is used as a child in a parent's view by `ChildName::props(props)`.
+pub trait ComponentWithProps: Component + 'static {
+    type Props: 'static;
+
+    /// Create the state of the child component from the first props.
+    fn with_props(comp: &Comp<Self>, props: Self::Props) -> Self;
+
+    /// Called with new props every time the parent's view updates.
+    fn props_changed(&mut self, props: Self::Props) -> ShouldRender;
+}
+
+#[doc(hidden)]
+pub struct ChildComp<C>
+where
+    C: ComponentWithProps,
+{
+    child: Option<(RcComp<C>, CompNode, CallbackArg<C::Props>)>,
+    pub comp_marker: web_sys::Node,
+}
+
+impl<C> ChildComp<C>
+where
+    C: ComponentWithProps,
+{
+    /// The child component will be created in the first update
+    pub fn new(comp_marker: web_sys::Node) -> Self {
+        Self {
+            child: None,
+            comp_marker,
+        }
+    }
+
+    pub fn with_props(parent: &WsElement, comp_marker: web_sys::Node, props: C::Props) -> Self {
+        let mut child_comp = Self::new(comp_marker);
+        child_comp.create_child(parent, props);
+        child_comp
+    }
+
+    fn create_child(&mut self, parent: &WsElement, props: C::Props) {
+        let rc_comp = RcComp::new(|comp| C::with_props(&comp, props));
+        let comp_node = rc_comp.create_comp_node(parent, self.comp_marker.clone());
+        let props_changed = rc_comp.comp().callback_arg(C::props_changed);
+        self.child = Some((rc_comp, comp_node, props_changed));
+    }
+
+    pub fn update(&mut self, parent: &WsElement, props: C::Props) {
+        match self.child.as_ref() {
+            Some((_, _, props_changed)) => props_changed.call(props),
+            None => self.create_child(parent, props),
+        }
+    }
+
+    pub fn remove_from(&self, parent: &WsElement) {
+        if let Some((_, comp_node, _)) = self.child.as_ref() {
+            parent.remove_child(&comp_node.root_element);
+        }
+        parent.remove_child(&self.comp_marker);
+    }
+}
+
+struct CompListItem<C>
+where
+    C: ComponentWithProps,
+{
+    // Dropping the `RcComp` disposes the child component
+    _rc_comp: RcComp<C>,
+    root_element: WsElement,
+    props_changed: CallbackArg<C::Props>,
+    // The props are already given to `with_props` when the item is created
+    just_created: bool,
+}
+
+impl<C> ItemViewState for CompListItem<C>
+where
+    C: ComponentWithProps,
+{
+    fn root_element(&self) -> &WsElement {
+        &self.root_element
+    }
+}
+
+/// A keyed list of child components, created by
+/// `spair_comp_list(items, |item| -> &Key {...}, |item| ChildName::props(...))`.
+/// Each key has its own component that is reused across updates. Items are the root elements
+/// of the components, so no template is cloned for them.
+#[doc(hidden)]
+pub struct CompList<K, C>
+where
+    K: Clone + Eq + Hash,
+    C: ComponentWithProps,
+{
+    list: KeyedList<K, CompListItem<C>>,
+}
+
+impl<K, C> CompList<K, C>
+where
+    K: Clone + Eq + Hash,
+    C: ComponentWithProps,
+{
+    pub fn new(
+        parent: &WsElement,
+        end_node_marker_for_partial_list: Option<web_sys::Node>,
+    ) -> Self {
+        Self {
+            list: KeyedList::without_template(parent, end_node_marker_for_partial_list),
+        }
+    }
+
+    pub fn end_node(&self) -> Option<&web_sys::Node> {
+        self.list.end_node()
+    }
+
+    pub fn update<I>(
+        &mut self,
+        item_data: impl Iterator<Item = I>,
+        get_key_fn: impl Fn(&I) -> &K,
+        get_props_fn: impl Fn(&I) -> C::Props,
+    ) {
+        // `mounted` hooks of new items are queued until the items are inserted
+        execute_then_the_update_queue(|| {
+            self.list.update_items(
+                item_data,
+                get_key_fn,
+                |item| {
+                    let props = get_props_fn(item);
+                    let rc_comp = RcComp::new(|comp| C::with_props(&comp, props));
+                    rc_comp.set_mounted_later();
+                    CompListItem {
+                        root_element: rc_comp.root_element(),
+                        props_changed: rc_comp.comp().callback_arg(C::props_changed),
+                        _rc_comp: rc_comp,
+                        just_created: true,
+                    }
+                },
+                |item_view_state, item| {
+                    if std::mem::take(&mut item_view_state.just_created) {
+                        return;
+                    }
+                    item_view_state.props_changed.call(get_props_fn(&item));
+                },
+            )
+        });
+    }
+}
@@ -1,14 +1,24 @@
 use std::{
-    cell::{Cell, RefCell},
+    any::Any,
+    cell::{Cell, OnceCell, RefCell},
     collections::VecDeque,
+    future::Future,
     rc::{Rc, Weak},
 };
 
 use wasm_bindgen::UnwrapThrowExt;
 
+#[cfg(feature = "serde")]
+use crate::recorder::Recorder;
 use crate::{
+    context::ContextScope,
     dom::{Element, WsElement},
-    routing::{Route, get_current_location, setup_routing},
+    history,
+    listener::{ListenerHandle, Listeners},
+    profiler,
+    routing::{Route, Router, get_current_location, setup_routing},
+    store::SubscriptionHandle,
+    task::{TaskHandle, Tasks},
 };
 
 /// Start a component as an app without routing functionality.
@@ -17,6 +27,7 @@ where
     C: Component + 'static,
 {
     let rc_comp = create_component(new_state, |_, _: ()| {}, |_, _| {});
+    rc_comp.set_mounted_n_call_hook();
     std::mem::forget(rc_comp);
 }
 
@@ -27,6 +38,7 @@ where
     R: Route + 'static,
 {
     let rc_comp = create_component(new_state, set_route, setup_routing);
+    rc_comp.set_mounted_n_call_hook();
     std::mem::forget(rc_comp);
 }
 
@@ -39,26 +51,44 @@ where
     C: Component + 'static,
     R: Route,
 {
-    let comp_data = CompData {
-        mounted: false,
-        data: None,
-    };
-    let rc_comp = RcComp(Rc::new(RefCell::new(comp_data)));
-    let mut state = new_state(rc_comp.comp());
+    let rc_comp = RcComp(Rc::new(CompData::new()));
+    // Callbacks called from `new_state` are queued until the component is created
+    execute_then_the_update_queue(|| {
+        rc_comp.0.scope.enter(|| {
+            let mut state = new_state(rc_comp.comp());
+
+            let route = R::from_location(&get_current_location());
+            set_route(&mut state, route);
+            setup_routing(set_route, rc_comp.comp());
+
+            finalize_rc_comp(&rc_comp, state);
+        })
+    });
+    rc_comp
+}
 
-    let route = R::from_location(&get_current_location());
-    set_route(&mut state, route);
-    setup_routing(set_route, rc_comp.comp());
+/// When a component's view is updated after a callback returns `ShouldRender::Yes`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Update the view right after each callback.
+    #[default]
+    Immediate,
+    /// Mutate the state right away, but update the view at most once per animation frame.
+    AnimationFrame,
+}
 
-    finalize_rc_comp(rc_comp, state)
+/// Set the render mode for components that do not have their own render mode.
+pub fn set_default_render_mode(mode: RenderMode) {
+    DEFAULT_RENDER_MODE.with(|default_mode| default_mode.set(mode));
 }
 
 thread_local! {
+    static DEFAULT_RENDER_MODE: Cell<RenderMode> = const { Cell::new(RenderMode::Immediate) };
     static UPDATE_QUEUE_IS_IN_EXECUTING: Cell<bool> = const { Cell::new(false) };
     static UPDATE_QUEUE: RefCell<VecDeque<Box<dyn FnOnce()>>> = RefCell::new(VecDeque::new());
 }
 
-fn is_update_queue_executing() -> bool {
+pub(crate) fn is_update_queue_executing() -> bool {
     UPDATE_QUEUE_IS_IN_EXECUTING.with(|executing| executing.get())
 }
 
@@ -66,7 +96,7 @@ fn update_queue_will_be_executing() -> bool {
     UPDATE_QUEUE_IS_IN_EXECUTING.with(|executing| !executing.replace(true))
 }
 
-fn put_callback_on_update_queue(callback: impl FnOnce() + 'static) {
+pub(crate) fn put_callback_on_update_queue(callback: impl FnOnce() + 'static) {
     UPDATE_QUEUE.with(|queue| match queue.try_borrow_mut() {
         Ok(mut queue) => queue.push_back(Box::new(callback)),
         Err(e) => {
@@ -75,19 +105,44 @@ fn put_callback_on_update_queue(callback: impl FnOnce() + 'static) {
     });
 }
 
+/// Execute the given fn as if it is a callback: callbacks called from it are queued, and the
+/// update queue is executed after it returns.
+pub(crate) fn execute_then_the_update_queue(f: impl FnOnce()) {
+    let need_to_execute_the_update_queue = update_queue_will_be_executing();
+
+    f();
+
+    if need_to_execute_the_update_queue {
+        execute_update_queue();
+    }
+}
+
 fn execute_update_queue() {
     UPDATE_QUEUE.with(|queue| {
         while let Some(callback) = queue.try_borrow_mut().ok().and_then(|mut v| v.pop_front()) {
             callback();
         }
     });
+    #[cfg(feature = "serde")]
+    crate::persisted::write_dirty_values();
     UPDATE_QUEUE_IS_IN_EXECUTING.with(|executing| executing.set(false));
 }
 
 pub trait Component: Sized {
     type ViewState: ComponentViewState;
+    /// `true` if the component has `Queued` fields that are rendered by `update_queued`.
+    const QUEUE_RENDER: bool = false;
     fn create(ccontext: &Context<Self>) -> Self::ViewState;
     fn update(view_state: &mut Self::ViewState, ucontext: &Context<Self>);
+    /// Only update the parts of the view that read changed `Queued` fields.
+    fn update_queued(_view_state: &mut Self::ViewState, _ucontext: &Context<Self>) {}
+    /// Called when the component's root element is inserted into the DOM.
+    fn mounted(&mut self, _comp: &Comp<Self>) {}
+    /// Called after the view is updated, `first` is `true` for the update in creating the
+    /// component. Callbacks called from here are queued and executed after it returns.
+    fn rendered(&mut self, _comp: &Comp<Self>, _first: bool) {}
+    /// Called when the component is unmounted or dropped, before its view is removed.
+    fn dispose(&mut self) {}
 }
 
 pub trait ComponentViewState {
@@ -98,8 +153,45 @@ struct CompData<C>
 where
     C: Component,
 {
-    mounted: bool,
-    data: Option<CompDataInner<C>>,
+    mounted: Cell<bool>,
+    disposed: Cell<bool>,
+    render_mode: Cell<Option<RenderMode>>,
+    queued_render_pending: Cell<bool>,
+    frame_render_pending: Cell<bool>,
+    tasks: RefCell<Tasks>,
+    listeners: RefCell<Listeners>,
+    routers: RefCell<Vec<Router>>,
+    context_subscriptions: RefCell<Vec<SubscriptionHandle>>,
+    scope: Rc<ContextScope>,
+    #[cfg(feature = "serde")]
+    recorder: RefCell<Option<Recorder<C>>>,
+    // The callback that handles messages of a `MessageComponent`
+    message_callback: OnceCell<Box<dyn Any>>,
+    data: RefCell<Option<CompDataInner<C>>>,
+}
+
+impl<C> CompData<C>
+where
+    C: Component,
+{
+    fn new() -> Self {
+        Self {
+            mounted: Cell::new(false),
+            disposed: Cell::new(false),
+            render_mode: Cell::new(None),
+            queued_render_pending: Cell::new(false),
+            frame_render_pending: Cell::new(false),
+            tasks: RefCell::new(Tasks::default()),
+            listeners: RefCell::new(Listeners::default()),
+            routers: RefCell::new(Vec::new()),
+            context_subscriptions: RefCell::new(Vec::new()),
+            scope: ContextScope::new_child_of_current(),
+            #[cfg(feature = "serde")]
+            recorder: RefCell::new(None),
+            message_callback: OnceCell::new(),
+            data: RefCell::new(None),
+        }
+    }
 }
 
 struct CompDataInner<C>
@@ -111,10 +203,34 @@ where
     view_state: C::ViewState,
 }
 
-pub struct RcComp<C>(Rc<RefCell<CompData<C>>>)
+impl<C> CompDataInner<C>
+where
+    C: Component,
+{
+    fn dispose(mut self) {
+        self.state.dispose();
+        self.root.detach();
+        // The view state is dropped here, all event listeners in it are removed.
+    }
+}
+
+impl<C> Drop for CompData<C>
+where
+    C: Component,
+{
+    fn drop(&mut self) {
+        self.tasks.get_mut().abort_all();
+        self.listeners.get_mut().remove_all();
+        if let Some(data) = self.data.get_mut().take() {
+            data.dispose();
+        }
+    }
+}
+
+pub struct RcComp<C>(Rc<CompData<C>>)
 where
     C: Component;
-pub struct Comp<C>(Weak<RefCell<CompData<C>>>)
+pub struct Comp<C>(Weak<CompData<C>>)
 where
     C: Component;
 
@@ -156,20 +272,22 @@ where
     }
 
     pub fn new(new_state: impl FnOnce(Comp<C>) -> C) -> Self {
-        let comp_data = CompData {
-            mounted: false,
-            data: None,
-        };
-        let rc_comp = RcComp(Rc::new(RefCell::new(comp_data)));
-        let comp = rc_comp.comp();
-        finalize_rc_comp(rc_comp, new_state(comp))
+        let rc_comp = RcComp(Rc::new(CompData::new()));
+        // Callbacks called from `new_state` are queued until the component is created
+        execute_then_the_update_queue(|| {
+            rc_comp
+                .0
+                .scope
+                .enter(|| finalize_rc_comp(&rc_comp, new_state(rc_comp.comp())))
+        });
+        rc_comp
     }
 
     pub fn root_element(&self) -> WsElement {
         self.0
+            .data
             .try_borrow()
             .expect_throw("Error on borrowing RcComp content to get the component's root element")
-            .data
             .as_ref()
             .expect_throw("RcComp CompData is empty")
             .root
@@ -178,13 +296,31 @@ where
 
     /// Set `mounted` to the given value, return the old value
     pub fn set_mounted(&self, value: bool) -> bool {
-        let mut comp_data = self
-            .0
-            .try_borrow_mut()
-            .expect_throw("Error on borrowing RcComp content to set mounted");
-        let old_value = comp_data.mounted;
-        comp_data.mounted = value;
-        old_value
+        self.0.mounted.replace(value)
+    }
+
+    /// Detach the component's root element, run `Component::dispose` and drop the view.
+    /// Callbacks that arrive after this are ignored. When the component is busy, e.g. this
+    /// is called from one of its callbacks, it is unmounted after the callback returns.
+    pub fn unmount(&self) {
+        let data = match self.0.data.try_borrow_mut() {
+            Ok(mut data) => data.take(),
+            Err(_) => {
+                let rc_comp = RcComp(Rc::clone(&self.0));
+                put_callback_on_update_queue(move || rc_comp.unmount());
+                return;
+            }
+        };
+        self.0.mounted.set(false);
+        self.0.disposed.set(true);
+        self.0.tasks.borrow_mut().abort_all();
+        self.0.listeners.borrow_mut().remove_all();
+        self.0.routers.borrow_mut().clear();
+        self.0.context_subscriptions.borrow_mut().clear();
+        // Dispose outside of the borrow, so callbacks from `dispose` are just ignored.
+        if let Some(data) = data {
+            data.dispose();
+        }
     }
 
     pub fn create_comp_node(&self, parent: &WsElement, comp_marker: web_sys::Node) -> CompNode {
@@ -193,33 +329,64 @@ where
             comp_marker,
         };
         parent.insert_new_node_before_a_node(&comp_node.root_element, Some(&comp_node.comp_marker));
-        self.set_mounted(true);
+        self.set_mounted_n_call_hook();
         comp_node
     }
 
     pub fn update_comp_node(&self, parent: &WsElement, comp_node: &mut CompNode) {
-        let mounted = self.set_mounted(true);
-        if mounted {
+        // An unmounted component has no view to insert, it is not shown again
+        if self.0.mounted.get() || self.0.disposed.get() {
             return;
         }
-        parent.remove_child(&comp_node.root_element);
+        comp_node.root_element.detach();
         comp_node.root_element = self.root_element();
         parent.insert_new_node_before_a_node(&comp_node.root_element, Some(&comp_node.comp_marker));
+        self.set_mounted_n_call_hook();
+    }
+
+    /// Mark the component as mounted and call its `mounted` hook after the update queue
+    /// is executed, when its root element is already inserted by the caller.
+    pub(crate) fn set_mounted_later(&self) {
+        let comp = self.comp();
+        put_callback_on_update_queue(move || {
+            if let Some(rc_comp) = comp.0.upgrade() {
+                RcComp(rc_comp).set_mounted_n_call_hook();
+            }
+        });
+    }
+
+    pub(crate) fn set_mounted_n_call_hook(&self) {
+        if self.set_mounted(true) {
+            return;
+        }
+        let comp = self.comp();
+        execute_then_the_update_queue(|| match self.0.data.try_borrow_mut() {
+            Ok(mut data) => {
+                if let Some(inner) = data.as_mut() {
+                    self.0.scope.enter(|| inner.state.mounted(&comp));
+                }
+            }
+            Err(e) => log::error!("Error on borrowing RcComp content to call `mounted`: {e}"),
+        });
     }
 }
 
-fn finalize_rc_comp<C>(rc_comp: RcComp<C>, state: C) -> RcComp<C>
+/// Must be executed by `execute_then_the_update_queue`, so that callbacks called while creating
+/// the view are queued until the data is stored.
+fn finalize_rc_comp<C>(rc_comp: &RcComp<C>, state: C)
 where
     C: Component + 'static,
 {
     let comp = rc_comp.comp();
+    let mut state = state;
     let context = comp.context(&state);
     let mut view_state = C::create(&context);
-    C::update(&mut view_state, &context);
+    profiler::profile_update::<C, _>(|| C::update(&mut view_state, &context));
+    state.rendered(&comp, true);
 
-    match rc_comp.0.try_borrow_mut() {
-        Ok(mut rc_comp) => {
-            rc_comp.data = Some(CompDataInner {
+    match rc_comp.0.data.try_borrow_mut() {
+        Ok(mut data) => {
+            *data = Some(CompDataInner {
                 root: view_state.root_element().ws_element().clone(),
                 state,
                 view_state,
@@ -227,7 +394,6 @@ where
         }
         _ => log::error!("Internal error: unable to mutable borrow rc_comp to set store its data"),
     }
-    rc_comp
 }
 
 pub enum ShouldRender {
@@ -247,7 +413,7 @@ trait CallbackArgTrait<A> {
 
 pub struct CallbackArg<A>(Rc<dyn CallbackArgTrait<A>>);
 #[derive(Clone)]
-pub struct Callback(CallbackArg<()>);
+pub struct Callback(pub(crate) CallbackArg<()>);
 
 impl<A> Clone for CallbackArg<A> {
     fn clone(&self) -> Self {
@@ -255,7 +421,20 @@ impl<A> Clone for CallbackArg<A> {
     }
 }
 
+struct CallbackFn<A>(Box<dyn Fn(A)>);
+
+impl<A> CallbackArgTrait<A> for CallbackFn<A> {
+    fn execute(&self, arg: A) {
+        (self.0)(arg);
+    }
+}
+
 impl<A: 'static> CallbackArg<A> {
+    /// A callback that is not tied to a component.
+    pub fn from_fn(f: impl Fn(A) + 'static) -> Self {
+        Self(Rc::new(CallbackFn(Box::new(f))))
+    }
+
     pub fn call(&self, arg: A) {
         if is_update_queue_executing() {
             self.queue(arg);
@@ -268,9 +447,20 @@ impl<A: 'static> CallbackArg<A> {
         let clone = Clone::clone(self);
         put_callback_on_update_queue(move || clone.0.execute(arg));
     }
+
+    /// Execute without checking the update queue, for a callback that wraps this callback
+    /// and is already checked by its own `call`.
+    pub(crate) fn execute(&self, arg: A) {
+        self.0.execute(arg);
+    }
 }
 
 impl Callback {
+    /// A callback that is not tied to a component.
+    pub fn from_fn(f: impl Fn() + 'static) -> Self {
+        Self(CallbackArg::from_fn(move |_| f()))
+    }
+
     pub fn call(&self) {
         if is_update_queue_executing() {
             self.0.queue(());
@@ -286,9 +476,24 @@ where
     C: Component,
 {
     comp: Comp<C>,
-    callback: Box<FnMutArg<C, A>>,
+    callback: Rc<FnMutArg<C, A>>,
 }
 
+impl<C, A> Clone for CallbackMutArgFn<C, A>
+where
+    C: Component,
+{
+    fn clone(&self) -> Self {
+        Self {
+            comp: self.comp.clone(),
+            callback: Rc::clone(&self.callback),
+        }
+    }
+}
+
+/// How many times a callback is put back on the update queue when its component is busy.
+const MAX_CALLBACK_RETRIES: u32 = 8;
+
 impl<C, A> CallbackArgTrait<A> for CallbackMutArgFn<C, A>
 where
     C: 'static + Component,
@@ -314,7 +519,7 @@ where
     {
         let cba = CallbackMutArgFn {
             comp: self.clone(),
-            callback: Box::new(move |state, _| callback_fn(state).into()),
+            callback: Rc::new(move |state, _| callback_fn(state).into()),
         };
         Callback(CallbackArg(Rc::new(cba)))
     }
@@ -329,11 +534,32 @@ where
     {
         let cba = CallbackMutArgFn {
             comp: self.clone(),
-            callback: Box::new(move |state, arg| callback_fn(state, arg).into()),
+            callback: Rc::new(move |state, arg| callback_fn(state, arg).into()),
         };
         CallbackArg(Rc::new(cba))
     }
 
+    /// The message callback that is created once by `new_callback` and stored in the component.
+    /// `None` if the component is dropped.
+    pub(crate) fn message_callback<A: 'static>(
+        &self,
+        new_callback: impl FnOnce() -> CallbackArg<A>,
+    ) -> Option<CallbackArg<A>> {
+        let comp_data = self.0.upgrade()?;
+        let callback = comp_data
+            .message_callback
+            .get_or_init(|| Box::new(new_callback()))
+            .downcast_ref::<CallbackArg<A>>()
+            .cloned();
+        if callback.is_none() {
+            log::error!(
+                "Internal error: the message callback is not a `CallbackArg<{}>`",
+                std::any::type_name::<A>()
+            );
+        }
+        callback
+    }
+
     fn execute_given_callback_then_the_update_queue<A: 'static>(
         &self,
         arg: A,
@@ -341,36 +567,275 @@ where
     ) {
         let need_to_execute_the_update_queue = update_queue_will_be_executing();
 
-        self.execute_callback(arg, cb_fn);
+        self.execute_callback(arg, cb_fn, 0);
 
         if need_to_execute_the_update_queue {
             execute_update_queue();
         }
     }
 
-    fn execute_callback<A: 'static>(&self, arg: A, cb_fn: &CallbackMutArgFn<C, A>) {
+    fn execute_callback<A: 'static>(&self, arg: A, cb_fn: &CallbackMutArgFn<C, A>, retries: u32) {
         let Some(this) = self.0.upgrade() else {
-            log::error!(
-                "Error on upgrading a WeakComp. A callback-executing has been interupted and discarded"
-            );
-            // Does the component has been disposed?
+            // The component has been dropped
+            return;
+        };
+        this.scope
+            .enter(|| self.execute_callback_in_scope(&this, arg, cb_fn, retries));
+    }
+
+    fn execute_callback_in_scope<A: 'static>(
+        &self,
+        comp_data: &CompData<C>,
+        arg: A,
+        cb_fn: &CallbackMutArgFn<C, A>,
+        retries: u32,
+    ) {
+        let Ok(mut data) = comp_data.data.try_borrow_mut() else {
+            // The component is busy, e.g. it is updating and one of its elements fires an event
+            self.requeue_callback(arg, cb_fn, retries);
             return;
         };
-        let Ok(mut comp_data) = this.try_borrow_mut() else {
+        let Some(inner) = data.as_mut() else {
+            if !comp_data.disposed.get() {
+                // The component is in construction
+                self.requeue_callback(arg, cb_fn, retries);
+            }
+            return;
+        };
+        profiler::record_callback::<C>();
+        history::next_callback_boundary();
+        let should_render = (cb_fn.callback)(&mut inner.state, arg);
+        #[cfg(feature = "serde")]
+        if let Some(recorder) = comp_data.recorder.borrow_mut().as_mut() {
+            recorder.record(&inner.state);
+        }
+        match should_render {
+            ShouldRender::Yes => {
+                let render_mode = comp_data
+                    .render_mode
+                    .get()
+                    .unwrap_or_else(|| DEFAULT_RENDER_MODE.with(|mode| mode.get()));
+                match render_mode {
+                    RenderMode::Immediate => {
+                        profiler::profile_update::<C, _>(|| {
+                            C::update(&mut inner.view_state, &self.context(&inner.state))
+                        });
+                        inner.state.rendered(self, false);
+                    }
+                    RenderMode::AnimationFrame => {
+                        if !comp_data.frame_render_pending.replace(true) {
+                            let comp = self.clone();
+                            crate::helper::request_animation_frame(move || {
+                                execute_then_the_update_queue(|| comp.execute_frame_render())
+                            });
+                        }
+                    }
+                }
+            }
+            ShouldRender::No => {
+                // Other callbacks in the queue may change more `Queued` fields, so
+                // the render is put at the end of the queue to be executed once.
+                if C::QUEUE_RENDER && !comp_data.queued_render_pending.replace(true) {
+                    let comp = self.clone();
+                    put_callback_on_update_queue(move || comp.execute_queued_render());
+                }
+            }
+        }
+    }
+
+    fn requeue_callback<A: 'static>(&self, arg: A, cb_fn: &CallbackMutArgFn<C, A>, retries: u32) {
+        if retries >= MAX_CALLBACK_RETRIES {
             log::error!(
-                "Error on trying borrow mut a CompData. A callback-executing has been interupted and discarded"
+                "A callback of component `{}` has been discarded: the component is still busy or not created yet after {retries} retries. Possibly a deadlock: the callback is executed while the component is borrowed by something that never finishes before the update queue is executed",
+                std::any::type_name::<C>()
             );
-            // cb_fn.queue(arg); To queue again, CallbackMutArgFn::callback must be changed to Rc
+            return;
+        }
+        let cb_fn = cb_fn.clone();
+        put_callback_on_update_queue(move || cb_fn.comp.execute_callback(arg, &cb_fn, retries + 1));
+    }
+
+    fn execute_frame_render(&self) {
+        let Some(this) = self.0.upgrade() else {
             return;
         };
-        let Some(comp_data) = comp_data.data.as_mut() else {
-            log::error!("Error: No state, no updaters");
-            // cb_fn.queue(arg); // same as above
+        this.frame_render_pending.set(false);
+        let Ok(mut data) = this.data.try_borrow_mut() else {
+            log::error!("Error on trying borrow mut a CompData. A frame render has been discarded");
             return;
         };
-        let should_render = (cb_fn.callback)(&mut comp_data.state, arg);
-        if let ShouldRender::Yes = should_render {
-            C::update(&mut comp_data.view_state, &self.context(&comp_data.state));
+        if let Some(inner) = data.as_mut() {
+            this.scope.enter(|| {
+                profiler::profile_update::<C, _>(|| {
+                    C::update(&mut inner.view_state, &self.context(&inner.state))
+                });
+                inner.state.rendered(self, false);
+            });
+        }
+    }
+
+    /// Set the render mode for this component, overriding the default render mode.
+    pub fn set_render_mode(&self, mode: RenderMode) {
+        if let Some(this) = self.0.upgrade() {
+            this.render_mode.set(Some(mode));
+        }
+    }
+
+    /// Run the future, then pass its output to `handler`. The task is aborted when the
+    /// returned handle is dropped, or when the component is unmounted.
+    pub fn spawn<T, S>(
+        &self,
+        future: impl Future<Output = T> + 'static,
+        handler: impl Fn(&mut C, T) -> S + 'static,
+    ) -> TaskHandle
+    where
+        T: 'static,
+        S: Into<ShouldRender>,
+    {
+        let callback = self.callback_arg(handler);
+        let handle = crate::task::spawn(async move { callback.call(future.await) });
+        match self.0.upgrade() {
+            Some(this) => this.tasks.borrow_mut().add(&handle),
+            None => handle.abort(),
+        }
+        handle
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn with_recorder<R>(
+        &self,
+        f: impl FnOnce(&mut Option<Recorder<C>>) -> R,
+    ) -> Option<R> {
+        let this = self.0.upgrade()?;
+        let mut recorder = this.recorder.borrow_mut();
+        Some(f(&mut recorder))
+    }
+
+    /// Read the state, `None` if the component is busy or has not been created yet.
+    pub(crate) fn with_state<R>(&self, f: impl FnOnce(&C) -> R) -> Option<R> {
+        let this = self.0.upgrade()?;
+        let data = this.data.try_borrow().ok()?;
+        data.as_ref().map(|inner| f(&inner.state))
+    }
+
+    /// The depth of the component in the component tree, the root component is at 0.
+    pub(crate) fn depth(&self) -> usize {
+        self.0.upgrade().map_or(0, |this| this.scope.depth())
+    }
+
+    /// Keep the router until the component is unmounted.
+    pub(crate) fn keep_router(&self, router: Router) {
+        if let Some(this) = self.0.upgrade() {
+            this.routers.borrow_mut().push(router);
+        }
+    }
+
+    /// Register a listener to remove it when the component is unmounted.
+    pub(crate) fn keep_listener(&self, handle: &ListenerHandle) {
+        match self.0.upgrade() {
+            Some(this) => this.listeners.borrow_mut().add(handle),
+            None => handle.remove(),
+        }
+    }
+
+    /// Same as `spawn`, but the task is kept by the component. Starting a new task with the
+    /// same `key` aborts the previous one, so only the output of the latest task is handled.
+    pub fn spawn_latest<T, S>(
+        &self,
+        key: &'static str,
+        future: impl Future<Output = T> + 'static,
+        handler: impl Fn(&mut C, T) -> S + 'static,
+    ) where
+        T: 'static,
+        S: Into<ShouldRender>,
+    {
+        let handle = self.spawn(future, handler);
+        if let Some(this) = self.0.upgrade() {
+            this.tasks.borrow_mut().set_latest(key, handle);
+        }
+    }
+
+    /// Abort the task started by `spawn_latest` with the given `key`.
+    pub fn abort_latest(&self, key: &'static str) {
+        if let Some(this) = self.0.upgrade() {
+            this.tasks.borrow_mut().abort_latest(key);
+        }
+    }
+
+    /// Provide a value to this component and its descendants, they can get a clone of it by
+    /// `Comp::use_context` or `spair::use_context`. The value is looked up by its type.
+    pub fn provide_context<T: 'static>(&self, value: T) {
+        if let Some(this) = self.0.upgrade() {
+            this.scope.provide(value);
+        }
+    }
+
+    /// Get a clone of the value of type `T` provided by this component or its ancestors.
+    pub fn use_context<T: Clone + 'static>(&self) -> Option<T> {
+        self.0.upgrade().and_then(|this| this.scope.get())
+    }
+
+    /// Mutate the value of type `T` provided by this component or its ancestors, then notify
+    /// the subscribers of the value. Log an error if no such value is found.
+    pub fn update_context<T: Clone + 'static>(&self, f: impl FnOnce(&mut T)) {
+        let Some(this) = self.0.upgrade() else {
+            return;
+        };
+        execute_then_the_update_queue(|| {
+            if !this.scope.update(f) {
+                log::error!(
+                    "No context value of type `{}` found to update",
+                    std::any::type_name::<T>()
+                );
+            }
+        });
+    }
+
+    /// Execute `handler` on this component every time the value of type `T` is updated by
+    /// `Comp::update_context`, until the component is unmounted. Return the current value, or
+    /// `None` if no such value is found.
+    pub fn subscribe_context<T, S>(&self, handler: impl Fn(&mut C, T) -> S + 'static) -> Option<T>
+    where
+        T: Clone + 'static,
+        S: Into<ShouldRender>,
+    {
+        let this = self.0.upgrade()?;
+        let comp = self.clone();
+        let callback = self.callback_arg(handler);
+        let (value, handle) = this.scope.subscribe(Box::new(move |value: &T| {
+            if !comp.is_alive() {
+                return false;
+            }
+            callback.call(value.clone());
+            true
+        }))?;
+        this.context_subscriptions.borrow_mut().push(handle);
+        Some(value)
+    }
+
+    /// `false` if the component has been dropped or unmounted.
+    pub(crate) fn is_alive(&self) -> bool {
+        self.0.upgrade().is_some_and(|this| !this.disposed.get())
+    }
+
+    fn execute_queued_render(&self) {
+        let Some(this) = self.0.upgrade() else {
+            return;
+        };
+        this.queued_render_pending.set(false);
+        let Ok(mut data) = this.data.try_borrow_mut() else {
+            log::error!(
+                "Error on trying borrow mut a CompData. A queued render has been discarded"
+            );
+            return;
+        };
+        if let Some(inner) = data.as_mut() {
+            this.scope.enter(|| {
+                profiler::profile_update::<C, _>(|| {
+                    C::update_queued(&mut inner.view_state, &self.context(&inner.state))
+                });
+                inner.state.rendered(self, false);
+            });
         }
     }
 }
@@ -411,3 +876,117 @@ pub struct CompNode {
     pub root_element: WsElement,
     pub comp_marker: web_sys::Node,
 }
+
+#[cfg(target_arch = "wasm32")]
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::{Comp, Component, ComponentViewState, Context, RcComp};
+    use crate::{WsElement, WsNodeFns, dom::Element};
+
+    struct TestViewState(Element);
+
+    impl ComponentViewState for TestViewState {
+        fn root_element(&self) -> &Element {
+            &self.0
+        }
+    }
+
+    struct Child;
+
+    impl Component for Child {
+        type ViewState = TestViewState;
+        fn create(_ccontext: &Context<Self>) -> Self::ViewState {
+            TestViewState(WsElement::create_element("span").create_element_with_capacity(0))
+        }
+        fn update(_view_state: &mut Self::ViewState, _ucontext: &Context<Self>) {}
+    }
+
+    fn child_count(parent: &WsElement) -> usize {
+        let mut count = 0;
+        let mut node = parent.get_ws_node_ref().first_child();
+        while let Some(child) = node {
+            count += 1;
+            node = child.next_sibling();
+        }
+        count
+    }
+
+    struct Counter {
+        value: u32,
+    }
+
+    impl Component for Counter {
+        type ViewState = TestViewState;
+        fn create(_ccontext: &Context<Self>) -> Self::ViewState {
+            TestViewState(WsElement::create_element("span").create_element_with_capacity(0))
+        }
+        fn update(_view_state: &mut Self::ViewState, _ucontext: &Context<Self>) {}
+    }
+
+    #[wasm_bindgen_test]
+    fn callback_from_new_state() {
+        let rc_comp = RcComp::new(|comp: Comp<Counter>| {
+            comp.callback(|state| state.value += 1).call();
+            Counter { value: 0 }
+        });
+        assert_eq!(rc_comp.comp().with_state(|state| state.value), Some(1));
+    }
+
+    struct Disposable {
+        disposed: Rc<Cell<bool>>,
+    }
+
+    impl Component for Disposable {
+        type ViewState = TestViewState;
+        fn create(_ccontext: &Context<Self>) -> Self::ViewState {
+            TestViewState(WsElement::create_element("span").create_element_with_capacity(0))
+        }
+        fn update(_view_state: &mut Self::ViewState, _ucontext: &Context<Self>) {}
+        fn dispose(&mut self) {
+            self.disposed.set(true);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn unmount_from_own_callback() {
+        let disposed = Rc::new(Cell::new(false));
+        let rc_comp = Rc::new(RcComp::new(|_| Disposable {
+            disposed: disposed.clone(),
+        }));
+        let parent = WsElement::create_element("div");
+        let marker = WsElement::create_element("i");
+        parent.insert_new_node_before_a_node(&marker, None::<&WsElement>);
+        let _comp_node = rc_comp.create_comp_node(&parent, marker.get_ws_node_ref().clone());
+        assert_eq!(child_count(&parent), 2);
+
+        let unmounting = Rc::clone(&rc_comp);
+        rc_comp
+            .comp()
+            .callback(move |_| unmounting.unmount())
+            .call();
+        assert!(disposed.get());
+        assert_eq!(child_count(&parent), 1);
+        assert!(rc_comp.comp().with_state(|_| ()).is_none());
+    }
+
+    #[wasm_bindgen_test]
+    fn update_comp_node_after_unmount() {
+        let parent = WsElement::create_element("div");
+        let marker = WsElement::create_element("i");
+        parent.insert_new_node_before_a_node(&marker, None::<&WsElement>);
+        let child = RcComp::new(|_| Child);
+        let mut comp_node = child.create_comp_node(&parent, marker.get_ws_node_ref().clone());
+        assert_eq!(child_count(&parent), 2);
+
+        child.unmount();
+        assert_eq!(child_count(&parent), 1);
+
+        // The parent is re-rendered while it still holds the `CompNode`
+        child.update_comp_node(&parent, &mut comp_node);
+        assert_eq!(child_count(&parent), 1);
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    any::{Any, TypeId},
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use crate::store::SubscriptionHandle;
+
+// A subscriber returns `false` if it is no longer alive
+pub(crate) type Subscriber<T> = Box<dyn Fn(&T) -> bool>;
+type SharedSubscriber<T> = Rc<dyn Fn(&T) -> bool>;
+
+/// Subscribers of a context value or a `Store`.
+pub(crate) struct Subscribers<T> {
+    next_id: Cell<u64>,
+    subscribers: RefCell<Vec<(u64, SharedSubscriber<T>)>>,
+}
+
+impl<T> Default for Subscribers<T> {
+    fn default() -> Self {
+        Self {
+            next_id: Cell::new(0),
+            subscribers: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl<T> Subscribers<T> {
+    /// Return an id to remove the subscriber.
+    pub fn add(&self, subscriber: Subscriber<T>) -> u64 {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.subscribers.borrow_mut().push((id, subscriber.into()));
+        id
+    }
+
+    pub fn remove(&self, id: u64) {
+        self.subscribers
+            .borrow_mut()
+            .retain(|(subscriber_id, _)| *subscriber_id != id);
+    }
+
+    /// Call every subscriber, the ones that are no longer alive are removed.
+    pub fn notify(&self, value: &T) {
+        // Subscribers may subscribe, unsubscribe or update the value again, so no borrow is
+        // held while calling them
+        let subscribers = self.subscribers.borrow().clone();
+        let dead: Vec<u64> = subscribers
+            .iter()
+            .filter(|(_, subscriber)| !subscriber(value))
+            .map(|(id, _)| *id)
+            .collect();
+        if !dead.is_empty() {
+            self.subscribers
+                .borrow_mut()
+                .retain(|(id, _)| !dead.contains(id));
+        }
+    }
+}
+
+/// Values provided by a component to its descendants. A component's scope is a child of the
+/// scope that is current when the component is created.
+pub(crate) struct ContextScope {
+    parent: Option<Rc<ContextScope>>,
+    // The depth of the component in the component tree, the root component is at 0
+    depth: usize,
+    values: RefCell<HashMap<TypeId, Rc<dyn Any>>>,
+}
+
+struct ContextValue<T> {
+    value: RefCell<T>,
+    subscribers: Subscribers<T>,
+}
+
+thread_local! {
+    static CURRENT_SCOPES: RefCell<Vec<Rc<ContextScope>>> = const { RefCell::new(Vec::new()) };
+}
+
+fn current_scope() -> Option<Rc<ContextScope>> {
+    CURRENT_SCOPES.with(|scopes| scopes.borrow().last().cloned())
+}
+
+/// Get a clone of the value of type `T` provided by the component that is currently
+/// executing (creating, updating or handling a callback) or by one of its ancestors.
+pub fn use_context<T: Clone + 'static>() -> Option<T> {
+    current_scope().and_then(|scope| scope.get())
+}
+
+impl ContextScope {
+    pub fn new_child_of_current() -> Rc<Self> {
+        let parent = current_scope();
+        Rc::new(Self {
+            depth: parent.as_ref().map_or(0, |parent| parent.depth + 1),
+            parent,
+            values: RefCell::new(HashMap::new()),
+        })
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Execute `f` with this scope as the current scope.
+    pub fn enter<R>(self: &Rc<Self>, f: impl FnOnce() -> R) -> R {
+        CURRENT_SCOPES.with(|scopes| scopes.borrow_mut().push(self.clone()));
+        let output = f();
+        CURRENT_SCOPES.with(|scopes| scopes.borrow_mut().pop());
+        output
+    }
+
+    pub fn provide<T: 'static>(&self, value: T) {
+        let value = ContextValue {
+            value: RefCell::new(value),
+            subscribers: Subscribers::default(),
+        };
+        self.values
+            .borrow_mut()
+            .insert(TypeId::of::<T>(), Rc::new(value));
+    }
+
+    fn find<T: 'static>(&self) -> Option<Rc<ContextValue<T>>> {
+        let value = self
+            .values
+            .borrow()
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .and_then(|value| value.downcast::<ContextValue<T>>().ok());
+        match value {
+            Some(value) => Some(value),
+            None => self.parent.as_ref().and_then(|parent| parent.find()),
+        }
+    }
+
+    pub fn get<T: Clone + 'static>(&self) -> Option<T> {
+        self.find::<T>().map(|value| value.value.borrow().clone())
+    }
+
+    /// Mutate the value then notify its subscribers. Return `false` if no value is found.
+    pub fn update<T: Clone + 'static>(&self, f: impl FnOnce(&mut T)) -> bool {
+        let Some(value) = self.find::<T>() else {
+            return false;
+        };
+        f(&mut value.value.borrow_mut());
+        let new_value = value.value.borrow().clone();
+        value.subscribers.notify(&new_value);
+        true
+    }
+
+    /// Return the current value and a handle that removes the subscriber when it is dropped,
+    /// or `None` (and the subscriber is not added) if no value is found.
+    pub fn subscribe<T: Clone + 'static>(
+        &self,
+        subscriber: Subscriber<T>,
+    ) -> Option<(T, SubscriptionHandle)> {
+        let value = self.find::<T>()?;
+        let id = value.subscribers.add(subscriber);
+        let current_value = value.value.borrow().clone();
+        let value = Rc::downgrade(&value);
+        let handle = SubscriptionHandle::new(move || {
+            if let Some(value) = value.upgrade() {
+                value.subscribers.remove(id);
+            }
+        });
+        Some((current_value, handle))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::ContextScope;
+
+    #[test]
+    fn drop_subscription_handle() {
+        let scope = ContextScope::new_child_of_current();
+        scope.provide(0u32);
+        let notified = Rc::new(Cell::new(0));
+        let counter = notified.clone();
+        let (value, handle) = scope
+            .subscribe(Box::new(move |value: &u32| {
+                counter.set(*value);
+                true
+            }))
+            .unwrap();
+        assert_eq!(value, 0);
+
+        scope.update(|value: &mut u32| *value = 1);
+        assert_eq!(notified.get(), 1);
+
+        drop(handle);
+        scope.update(|value: &mut u32| *value = 2);
+        assert_eq!(notified.get(), 1);
+    }
+}
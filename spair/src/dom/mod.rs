@@ -9,7 +9,7 @@ use web_sys::{
 
 use crate::events::EventListener;
 use crate::helper::{self, InputElementFromCurrentInputEvent};
-use crate::{component::CallbackArg, routing::Route};
+use crate::{component::CallbackArg, profiler, routing::Route};
 
 pub mod text;
 
@@ -67,6 +67,7 @@ impl WsNodeFns for web_sys::Node {
     }
 }
 
+#[derive(Clone)]
 pub struct TemplateElement(HtmlTemplateElement);
 impl TemplateElement {
     pub fn new(html: &str) -> Self {
@@ -160,7 +161,17 @@ enum Attribute {
     I32(i32),
     Str(String),
     OptionString(Option<String>),
-    EventListener(Box<dyn EventListener>),
+    EventListener(&'static str, Box<dyn EventListener>),
+}
+
+impl Drop for Element {
+    fn drop(&mut self) {
+        for attribute in self.attributes.iter() {
+            if let Attribute::EventListener(name, listener) = attribute {
+                self.element.remove_event_listener(name, listener.as_ref());
+            }
+        }
+    }
 }
 
 impl WsElement {
@@ -176,10 +187,12 @@ impl WsElement {
     }
 
     pub fn clear_text_content(&self) {
+        profiler::record_dom_write();
         self.0.set_text_content(None);
     }
 
     pub fn set_id(&self, id: &str) {
+        profiler::record_dom_write();
         self.0.set_id(id);
     }
 
@@ -219,6 +232,7 @@ impl WsElement {
         new_node: &impl WsNodeFns,
         next_sibling: Option<&impl WsNodeFns>,
     ) {
+        profiler::record_dom_write();
         if let Err(e) = self.0.insert_before(
             new_node.get_ws_node_ref(),
             next_sibling.map(|v| v.get_ws_node_ref()),
@@ -227,13 +241,21 @@ impl WsElement {
         };
     }
 
+    /// Remove the element from its parent, do nothing if it has no parent.
+    pub fn detach(&self) {
+        profiler::record_dom_write();
+        self.0.remove();
+    }
+
     pub fn remove_child(&self, child: &impl WsNodeFns) {
+        profiler::record_dom_write();
         if let Err(e) = self.0.remove_child(child.get_ws_node_ref()) {
             log::error!("Error on removing child node: {e:?}");
         }
     }
 
     pub fn set_bool_attribute(&self, name: &str, value: bool) {
+        profiler::record_dom_write();
         let name = wasm_bindgen::intern(name);
         if value {
             if let Err(e) = self.0.set_attribute(name, "") {
@@ -245,6 +267,7 @@ impl WsElement {
     }
 
     pub fn set_str_attribute(&self, name: &str, value: &str) {
+        profiler::record_dom_write();
         let name = wasm_bindgen::intern(name);
         if let Err(e) = self.0.set_attribute(name, value) {
             log::error!("Error on setting an attributel {name}={value}: {e:?}");
@@ -261,10 +284,12 @@ impl WsElement {
     }
 
     pub fn unsafely_set_inner_html(&self, value: &str) {
+        profiler::record_dom_write();
         self.0.set_inner_html(value);
     }
 
     pub fn add_class(&self, class_name: &str) {
+        profiler::record_dom_write();
         let class_name = wasm_bindgen::intern(class_name);
         if let Err(e) = self.0.class_list().add_1(class_name) {
             log::error!("Error on adding a class name: {e:?}");
@@ -272,6 +297,7 @@ impl WsElement {
     }
 
     fn remove_class(&self, class_name: &str) {
+        profiler::record_dom_write();
         let class_name = wasm_bindgen::intern(class_name);
         if let Err(e) = self.0.class_list().remove_1(class_name) {
             log::error!("Error on removing a class named `{class_name}`: {e:?}");
@@ -295,6 +321,7 @@ impl WsElement {
     }
 
     fn set_select_value_str(&self, value: &str) {
+        profiler::record_dom_write();
         self.0.unchecked_ref::<HtmlSelectElement>().set_value(value);
     }
 
@@ -306,16 +333,19 @@ impl WsElement {
     }
 
     pub fn set_input_checked(&self, value: bool) {
+        profiler::record_dom_write();
         self.0
             .unchecked_ref::<HtmlInputElement>()
             .set_checked(value);
     }
 
     pub fn set_input_value(&self, value: &str) {
+        profiler::record_dom_write();
         self.0.unchecked_ref::<HtmlInputElement>().set_value(value);
     }
 
     pub fn set_textarea_value(&self, value: &str) {
+        profiler::record_dom_write();
         self.0
             .unchecked_ref::<HtmlTextAreaElement>()
             .set_value(value);
@@ -325,6 +355,7 @@ impl WsElement {
     }
 
     pub fn set_option_value(&self, value: &str) {
+        profiler::record_dom_write();
         self.0.unchecked_ref::<HtmlOptionElement>().set_value(value);
     }
 }
@@ -337,11 +368,11 @@ impl Element {
     pub fn add_event_listener(
         &mut self,
         index: usize,
-        name: &str,
+        name: &'static str,
         listener: Box<dyn EventListener>,
     ) {
         if let Some(old_listener) = self.attributes.get_mut(index) {
-            if let Attribute::EventListener(old_listener) = old_listener {
+            if let Attribute::EventListener(_, old_listener) = old_listener {
                 self.element
                     .remove_event_listener(name, old_listener.as_ref());
                 self.element.add_event_listener(name, listener.as_ref());
@@ -351,7 +382,8 @@ impl Element {
             }
         } else if self.attributes.len() == index {
             self.element.add_event_listener(name, listener.as_ref());
-            self.attributes.push(Attribute::EventListener(listener));
+            self.attributes
+                .push(Attribute::EventListener(name, listener));
         } else {
             log::error!(
                 "Internal error: A new attribute expected being added at the end of the list (index = {}), but the given index = {index}",
@@ -726,7 +758,7 @@ macro_rules! create_event_methods {
     ($($EventArgType:ident { $($event_name:ident)+ })+) => {$(
         impl Element {
             #[allow(non_snake_case)]
-            fn $EventArgType(&mut self, index: usize, event_name: &str, callback: CallbackArg<web_sys::$EventArgType>) {
+            fn $EventArgType(&mut self, index: usize, event_name: &'static str, callback: CallbackArg<web_sys::$EventArgType>) {
                 self.add_event_listener(
                     index,
                     event_name,
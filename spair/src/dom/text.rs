@@ -174,6 +174,7 @@ impl WsText {
     }
 
     fn set_text_content(&self, text: &str) {
+        crate::profiler::record_dom_write();
         self.0.set_text_content(Some(text));
     }
 }
@@ -25,6 +25,13 @@ pub fn create_element(tag: &str) -> web_sys::Element {
         .expect_throw("create_element")
 }
 
+pub fn request_animation_frame(f: impl FnOnce() + 'static) {
+    let f = wasm_bindgen::closure::Closure::once_into_js(f);
+    if let Err(e) = WINDOW.with(|window| window.request_animation_frame(f.unchecked_ref())) {
+        log::error!("Error on requesting an animation frame: {e:?}");
+    }
+}
+
 pub trait ElementFromCurrentEventTarget {
     fn get_current_target(&self) -> EventTarget;
     fn current_target_as_select(&self) -> HtmlSelectElement {
@@ -0,0 +1,219 @@
+use std::{cell::Cell, collections::VecDeque, ops::Deref};
+
+use crate::component::{CallbackArg, Comp, Component, ShouldRender};
+
+thread_local! {
+    static CALLBACK_BOUNDARY: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Called before every callback is executed, all edits of a `History` in the same callback
+/// are recorded as one step.
+pub(crate) fn next_callback_boundary() {
+    CALLBACK_BOUNDARY.with(|boundary| boundary.set(boundary.get() + 1));
+}
+
+fn current_callback_boundary() -> u64 {
+    CALLBACK_BOUNDARY.with(|boundary| boundary.get())
+}
+
+const DEFAULT_CAPACITY: usize = 100;
+
+/// A state field with undo/redo. The value before the first `edit` in a callback is kept
+/// as an undo step.
+pub struct History<T> {
+    present: T,
+    past: VecDeque<T>,
+    future: Vec<T>,
+    capacity: usize,
+    coalesce_ms: Option<f64>,
+    last_edit_boundary: Option<u64>,
+    last_edit_time: f64,
+}
+
+/// A component that has a `History`, so `Comp::undo` and `Comp::redo` can be used in its view.
+pub trait Undoable: Component + 'static {
+    type Snapshot: Clone;
+
+    fn history(&mut self) -> &mut History<Self::Snapshot>;
+}
+
+impl<T: Clone> History<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            present: value,
+            past: VecDeque::new(),
+            future: Vec::new(),
+            capacity: DEFAULT_CAPACITY,
+            coalesce_ms: None,
+            last_edit_boundary: None,
+            last_edit_time: 0.0,
+        }
+    }
+
+    /// Keep at most `capacity` undo steps, the oldest ones are dropped. The default is 100.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Edits that follow the previous edit within `ms` milliseconds are grouped into the
+    /// same undo step, e.g. keystrokes in a text input.
+    pub fn coalesce_within(mut self, ms: i32) -> Self {
+        self.coalesce_ms = Some(f64::from(ms));
+        self
+    }
+
+    /// Mutable access to the value. Redo steps are cleared.
+    pub fn edit(&mut self) -> &mut T {
+        let boundary = current_callback_boundary();
+        if self.last_edit_boundary == Some(boundary) {
+            return &mut self.present;
+        }
+        self.last_edit_boundary = Some(boundary);
+        self.future.clear();
+
+        if let Some(coalesce_ms) = self.coalesce_ms {
+            let now = js_sys::Date::now();
+            let last_edit_time = std::mem::replace(&mut self.last_edit_time, now);
+            if !self.past.is_empty() && now - last_edit_time < coalesce_ms {
+                return &mut self.present;
+            }
+        }
+
+        self.past.push_back(self.present.clone());
+        while self.past.len() > self.capacity {
+            self.past.pop_front();
+        }
+        &mut self.present
+    }
+
+    pub fn set(&mut self, value: T) {
+        *self.edit() = value;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.past.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.future.is_empty()
+    }
+
+    /// Return `false` if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(value) = self.past.pop_back() else {
+            return false;
+        };
+        self.future
+            .push(std::mem::replace(&mut self.present, value));
+        // The next edit must start a new step
+        self.last_edit_boundary = None;
+        self.last_edit_time = 0.0;
+        true
+    }
+
+    /// Return `false` if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(value) = self.future.pop() else {
+            return false;
+        };
+        self.past
+            .push_back(std::mem::replace(&mut self.present, value));
+        self.last_edit_boundary = None;
+        self.last_edit_time = 0.0;
+        true
+    }
+
+    /// Drop all undo and redo steps.
+    pub fn clear(&mut self) {
+        self.past.clear();
+        self.future.clear();
+        self.last_edit_boundary = None;
+    }
+}
+
+impl<T> Deref for History<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.present
+    }
+}
+
+impl<C> Comp<C>
+where
+    C: Undoable,
+{
+    /// A callback that undoes the last step of the component's history.
+    pub fn undo<A: 'static>(&self) -> CallbackArg<A> {
+        self.callback_arg(|state: &mut C, _: A| should_render(state.history().undo()))
+    }
+
+    /// A callback that redoes the last undone step of the component's history.
+    pub fn redo<A: 'static>(&self) -> CallbackArg<A> {
+        self.callback_arg(|state: &mut C, _: A| should_render(state.history().redo()))
+    }
+}
+
+fn should_render(changed: bool) -> ShouldRender {
+    match changed {
+        true => ShouldRender::Yes,
+        false => ShouldRender::No,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{History, next_callback_boundary};
+
+    #[test]
+    fn edits_in_a_callback_are_one_step() {
+        let mut history = History::new(0);
+        next_callback_boundary();
+        *history.edit() += 1;
+        *history.edit() += 1;
+        next_callback_boundary();
+        *history.edit() += 1;
+        assert_eq!(*history, 3);
+
+        assert!(history.undo());
+        assert_eq!(*history, 2);
+        assert!(history.undo());
+        assert_eq!(*history, 0);
+        assert!(!history.undo());
+
+        assert!(history.redo());
+        assert_eq!(*history, 2);
+        assert!(history.redo());
+        assert_eq!(*history, 3);
+        assert!(!history.redo());
+    }
+
+    #[test]
+    fn an_edit_clears_redo_steps() {
+        let mut history = History::new("a".to_string());
+        next_callback_boundary();
+        history.set("b".to_string());
+        assert!(history.undo());
+        assert!(history.can_redo());
+
+        // The same callback boundary as the undone edit, but it is still a new step
+        history.set("c".to_string());
+        assert!(!history.can_redo());
+        assert!(history.undo());
+        assert_eq!(history.as_str(), "a");
+    }
+
+    #[test]
+    fn capacity_drops_the_oldest_steps() {
+        let mut history = History::new(0).with_capacity(2);
+        for _ in 0..5 {
+            next_callback_boundary();
+            *history.edit() += 1;
+        }
+        assert!(history.undo());
+        assert!(history.undo());
+        assert!(!history.undo());
+        assert_eq!(*history, 3);
+    }
+}
@@ -11,7 +11,8 @@ where
     K: Clone + Eq + Hash,
 {
     parent_element: WsElement,
-    template: TemplateElement,
+    // `None` if items are not created from a template, e.g. items of a `CompList`
+    template: Option<TemplateElement>,
 
     active_items: Vec<Option<KeyVS<K, VS>>>,
     buffer_items: Vec<Option<KeyVS<K, VS>>>,
@@ -39,11 +40,10 @@ where
     K: Clone + Eq + Hash,
     VS: ItemViewState,
     GK: Fn(&I) -> &K,
-    CV: Fn(DocumentFragment, &I) -> VS,
+    CV: Fn(&I) -> VS,
     UV: Fn(&mut VS, I),
 {
     parent_element: &'a WsElement,
-    template: &'a TemplateElement,
 
     get_key_fn: GK,
     create_view_fn: CV,
@@ -67,10 +67,20 @@ where
         parent_element: &WsElement,
         end_node_marker_for_partial_list: Option<web_sys::Node>,
         template_string: &str,
+    ) -> Self {
+        let mut list = Self::without_template(parent_element, end_node_marker_for_partial_list);
+        list.template = Some(TemplateElement::new(template_string));
+        list
+    }
+
+    /// A list whose items are created by `update_items` without a template.
+    pub(crate) fn without_template(
+        parent_element: &WsElement,
+        end_node_marker_for_partial_list: Option<web_sys::Node>,
     ) -> Self {
         Self {
             parent_element: parent_element.clone(),
-            template: TemplateElement::new(template_string),
+            template: None,
             end_node_marker_for_partial_list,
 
             active_items: Vec::new(),
@@ -93,6 +103,30 @@ where
         GK: Fn(&I) -> &K,
         CV: Fn(DocumentFragment, &I) -> VS,
         UV: Fn(&mut VS, I),
+    {
+        let template = self
+            .template
+            .clone()
+            .expect_throw("Internal error: KeyedList::update on a list without a template");
+        self.update_items(
+            item_data,
+            get_key_fn,
+            move |item| create_view_fn(template.fragment_clone(), item),
+            update_view_fn,
+        );
+    }
+
+    /// Like `update`, but `create_view_fn` creates the view state of a new item by itself.
+    pub(crate) fn update_items<I, GK, CV, UV>(
+        &mut self,
+        item_data: impl Iterator<Item = I>,
+        get_key_fn: GK,
+        create_view_fn: CV,
+        update_view_fn: UV,
+    ) where
+        GK: Fn(&I) -> &K,
+        CV: Fn(&I) -> VS,
+        UV: Fn(&mut VS, I),
     {
         // Current implementation requires knowing the exact number in advance.
         let item_data: Vec<_> = item_data.collect();
@@ -114,7 +148,6 @@ where
 
         let mut keyed_list_updater = KeyedListUpdater {
             parent_element: &self.parent_element,
-            template: &self.template,
             old_list: self
                 .buffer_items
                 .iter_mut()
@@ -157,7 +190,7 @@ where
     K: Clone + Eq + Hash,
     VS: ItemViewState,
     GK: Fn(&I) -> &K,
-    CV: Fn(DocumentFragment, &I) -> VS,
+    CV: Fn(&I) -> VS,
     UV: Fn(&mut VS, I),
 {
     fn update(&mut self, item_data: Vec<I>) {
@@ -461,7 +494,7 @@ where
     }
 
     fn render_new_item(&self, item_data: I) -> VS {
-        let mut view_state = (self.create_view_fn)(self.template.fragment_clone(), &item_data);
+        let mut view_state = (self.create_view_fn)(&item_data);
         (self.update_view_fn)(&mut view_state, item_data);
 
         let next_sibling = self.end_flag_for_the_next_rendered_item_bottom_up.as_ref();
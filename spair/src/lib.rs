@@ -1,29 +1,68 @@
 #[cfg(test)]
 wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
 
+mod app;
+mod callback;
+mod child_comp;
 mod component;
+mod context;
 mod dom;
 mod events;
 mod helper;
+mod history;
 mod keyed_list;
 mod list;
+mod listener;
+mod message;
 mod name_hints;
+#[cfg(feature = "serde")]
+mod persisted;
+pub mod profiler;
+mod query;
+mod queue_render;
+#[cfg(feature = "serde")]
+mod recorder;
+mod resource;
 mod routing;
+mod store;
+mod task;
+mod timer;
 mod ws_node_ref;
 
 pub use web_sys;
 
+pub use app::{AppHandle, mount, mount_at_selector};
+pub use child_comp::{ChildComp, CompList, ComponentWithProps};
 pub use component::{
     Callback, CallbackArg, Comp, CompNode, Component, ComponentViewState, Context, RcComp,
-    ShouldRender, start_app, start_app_with_routing,
+    RenderMode, ShouldRender, set_default_render_mode, start_app, start_app_with_routing,
 };
+pub use context::use_context;
 pub use dom::{
     Element, TemplateElement, WsElement, WsNode, WsNodeFns,
     text::{RenderOptionWithDefault, Text, WsText},
 };
+pub use history::{History, Undoable};
 pub use keyed_list::{ItemViewState, KeyedList};
 pub use list::List;
-pub use routing::Route;
+pub use listener::ListenerHandle;
+pub use message::MessageComponent;
+#[cfg(feature = "serde")]
+pub use persisted::{Persisted, PersistedMut, StorageArea};
+pub use query::{FromQuery, Query, QueryError, QueryValue, ToQuery, percent_decode};
+pub use queue_render::Queued;
+#[cfg(feature = "serde")]
+pub use recorder::{RecordState, Recording};
+pub use resource::{Resource, ResourceState};
+pub use routing::{
+    NavigationGuard, NestedRoute, Route, RouteFromUrl, back, forward, navigate, navigate_replace,
+    nested_route_url, percent_decode_path, percent_encode, route_segments, split_route_path,
+    url_parts,
+};
+pub use spair_macros::{Query, Route};
+pub use store::{Store, SubscriptionHandle};
+pub use task::TaskHandle;
+pub use timer::TimerHandle;
 pub use web_sys::DocumentFragment;
 pub use ws_node_ref::WsRef;
 
@@ -33,5 +72,6 @@ pub mod prelude {
     };
     pub use crate::helper::{ElementFromCurrentEventTarget, InputElementFromCurrentInputEvent};
     pub use crate::name_hints::*;
+    pub use crate::queue_render::Queued;
     pub use spair_macros::{create_view, impl_component};
 }
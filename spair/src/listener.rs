@@ -0,0 +1,162 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use wasm_bindgen::{JsCast, closure::Closure};
+use web_sys::EventTarget;
+
+use crate::{
+    component::{Comp, Component, ShouldRender},
+    helper::{DOCUMENT, WINDOW},
+};
+
+/// A handle to a listener added by `Comp::listen_window` or `Comp::listen_document`. The
+/// listener is removed when the handle is dropped or when the component is unmounted.
+#[must_use = "the listener is removed when its handle is dropped"]
+pub struct ListenerHandle(Rc<GlobalListener>);
+
+type EventClosure = Closure<dyn Fn(web_sys::Event)>;
+struct GlobalListener {
+    target: EventTarget,
+    event_name: String,
+    closure: RefCell<Option<EventClosure>>,
+}
+
+impl GlobalListener {
+    fn remove(&self) {
+        let Some(closure) = self.closure.take() else {
+            return;
+        };
+        if let Err(e) = self
+            .target
+            .remove_event_listener_with_callback(&self.event_name, closure.as_ref().unchecked_ref())
+        {
+            log::error!(
+                "Error on removing event listener for `{}`: {e:?}",
+                self.event_name
+            );
+        }
+    }
+}
+
+impl ListenerHandle {
+    /// Remove the listener.
+    pub fn remove(&self) {
+        self.0.remove();
+    }
+}
+
+impl Drop for ListenerHandle {
+    fn drop(&mut self) {
+        self.0.remove();
+    }
+}
+
+/// Listeners of a component, they are removed when the component is unmounted.
+#[derive(Default)]
+pub(crate) struct Listeners(Vec<Weak<GlobalListener>>);
+
+impl Listeners {
+    pub fn add(&mut self, handle: &ListenerHandle) {
+        self.0.retain(|listener| {
+            listener
+                .upgrade()
+                .is_some_and(|v| v.closure.borrow().is_some())
+        });
+        self.0.push(Rc::downgrade(&handle.0));
+    }
+
+    pub fn remove_all(&mut self) {
+        for listener in self.0.drain(..).filter_map(|listener| listener.upgrade()) {
+            listener.remove();
+        }
+    }
+}
+
+impl<C> Comp<C>
+where
+    C: 'static + Component,
+{
+    /// Listen to `event_name` (e.g. "resize", "keydown", "online") on `window`.
+    pub fn listen_window<E, S>(
+        &self,
+        event_name: &str,
+        handler: impl Fn(&mut C, E) -> S + 'static,
+    ) -> ListenerHandle
+    where
+        E: JsCast + 'static,
+        S: Into<ShouldRender>,
+    {
+        let target = WINDOW.with(|window| window.clone().into());
+        self.listen(target, event_name, handler)
+    }
+
+    /// Listen to `event_name` (e.g. "click", "visibilitychange") on `document`.
+    pub fn listen_document<E, S>(
+        &self,
+        event_name: &str,
+        handler: impl Fn(&mut C, E) -> S + 'static,
+    ) -> ListenerHandle
+    where
+        E: JsCast + 'static,
+        S: Into<ShouldRender>,
+    {
+        let target = DOCUMENT.with(|document| document.clone().into());
+        self.listen(target, event_name, handler)
+    }
+
+    fn listen<E, S>(
+        &self,
+        target: EventTarget,
+        event_name: &str,
+        handler: impl Fn(&mut C, E) -> S + 'static,
+    ) -> ListenerHandle
+    where
+        E: JsCast + 'static,
+        S: Into<ShouldRender>,
+    {
+        let callback = self.callback_arg(handler);
+        let name = event_name.to_string();
+        let closure = EventClosure::new(move |event: web_sys::Event| match event.dyn_into() {
+            Ok(event) => callback.call(event),
+            Err(event) => log::error!(
+                "The `{name}` event is not a `{}`, it is skipped: {event:?}",
+                std::any::type_name::<E>()
+            ),
+        });
+        self.add_listener(target, event_name, closure)
+    }
+
+    /// Listen to `event_name` on `window`, `handler` is executed right in the event, instead
+    /// of being queued like a callback. It is for events that must be handled before they
+    /// return, e.g. `beforeunload`.
+    pub(crate) fn listen_window_now(
+        &self,
+        event_name: &str,
+        handler: impl Fn(web_sys::Event) + 'static,
+    ) -> ListenerHandle {
+        let target = WINDOW.with(|window| window.clone().into());
+        self.add_listener(target, event_name, EventClosure::new(handler))
+    }
+
+    fn add_listener(
+        &self,
+        target: EventTarget,
+        event_name: &str,
+        closure: EventClosure,
+    ) -> ListenerHandle {
+        if let Err(e) =
+            target.add_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())
+        {
+            log::error!("Error on adding event listener for `{event_name}`: {e:?}");
+        }
+        let handle = ListenerHandle(Rc::new(GlobalListener {
+            target,
+            event_name: event_name.to_string(),
+            closure: RefCell::new(Some(closure)),
+        }));
+        self.keep_listener(&handle);
+        handle
+    }
+}
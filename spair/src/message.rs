@@ -0,0 +1,134 @@
+use crate::component::{CallbackArg, Comp, Component, ShouldRender};
+
+/// A component whose state transitions are all done in `handle`. Its view sends messages
+/// by `Comp::msg` and `Comp::msg_with` instead of closures that mutate the state.
+pub trait MessageComponent: Component + 'static {
+    type Msg: 'static;
+
+    fn handle(&mut self, msg: Self::Msg) -> ShouldRender;
+
+    /// Called with every message right before it is handled, e.g. to log or record messages
+    /// so they can be replayed later.
+    fn on_message(&self, _msg: &Self::Msg) {}
+}
+
+fn dispatch<C>(state: &mut C, msg: C::Msg) -> ShouldRender
+where
+    C: MessageComponent,
+{
+    state.on_message(&msg);
+    state.handle(msg)
+}
+
+impl<C> Comp<C>
+where
+    C: MessageComponent,
+{
+    /// Send a message to the component. It is handled like a callback, so it is queued if
+    /// the update queue is executing.
+    pub fn send(&self, msg: C::Msg) {
+        if let Some(sender) = self.message_sender() {
+            sender.call(msg);
+        }
+    }
+
+    // The callback that handles the messages, it is created once and stored in the component.
+    fn message_sender(&self) -> Option<CallbackArg<C::Msg>> {
+        self.message_callback(|| self.callback_arg(dispatch::<C>))
+    }
+
+    /// A callback that sends a clone of `msg`, the argument of the callback is ignored.
+    pub fn msg<A>(&self, msg: C::Msg) -> CallbackArg<A>
+    where
+        A: 'static,
+        C::Msg: Clone,
+    {
+        let sender = self.message_sender();
+        // `execute` because the returned callback already checked the update queue
+        CallbackArg::from_fn(move |_| {
+            if let Some(sender) = &sender {
+                sender.execute(msg.clone());
+            }
+        })
+    }
+
+    /// A callback that sends the message built from its argument.
+    pub fn msg_with<A>(&self, map: impl Fn(A) -> C::Msg + 'static) -> CallbackArg<A>
+    where
+        A: 'static,
+    {
+        let sender = self.message_sender();
+        CallbackArg::from_fn(move |arg| {
+            if let Some(sender) = &sender {
+                sender.execute(map(arg));
+            }
+        })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::MessageComponent;
+    use crate::{
+        WsElement,
+        component::{Component, ComponentViewState, Context, RcComp, ShouldRender},
+        dom::Element,
+    };
+
+    struct TestViewState(Element);
+
+    impl ComponentViewState for TestViewState {
+        fn root_element(&self) -> &Element {
+            &self.0
+        }
+    }
+
+    struct Counter {
+        value: i32,
+        log: RefCell<Vec<i32>>,
+    }
+
+    impl Component for Counter {
+        type ViewState = TestViewState;
+        fn create(_ccontext: &Context<Self>) -> Self::ViewState {
+            TestViewState(WsElement::create_element("span").create_element_with_capacity(0))
+        }
+        fn update(_view_state: &mut Self::ViewState, _ucontext: &Context<Self>) {}
+    }
+
+    impl MessageComponent for Counter {
+        type Msg = i32;
+
+        fn handle(&mut self, msg: i32) -> ShouldRender {
+            self.value += msg;
+            ShouldRender::Yes
+        }
+
+        fn on_message(&self, msg: &i32) {
+            self.log.borrow_mut().push(*msg);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn messages_go_through_on_message() {
+        let rc_comp = RcComp::new(|_| Counter {
+            value: 0,
+            log: RefCell::new(Vec::new()),
+        });
+        let comp = rc_comp.comp();
+        comp.send(2);
+        comp.msg(3).call(());
+        comp.msg_with(|n: i32| n * 2).call(5);
+
+        let (value, log) = comp
+            .with_state(|state| (state.value, state.log.borrow().clone()))
+            .unwrap();
+        assert_eq!(value, 15);
+        assert_eq!(log, [2, 3, 10]);
+    }
+}
@@ -0,0 +1,394 @@
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    ops::{Deref, DerefMut},
+    rc::{Rc, Weak},
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+use wasm_bindgen::{JsCast, UnwrapThrowExt, closure::Closure};
+use web_sys::{Storage, StorageEvent};
+
+use crate::{
+    component::{Comp, Component, ShouldRender, is_update_queue_executing},
+    helper::WINDOW,
+    listener::ListenerHandle,
+};
+
+thread_local! {
+    // Writers whose values were changed in the current callback cycle
+    static DIRTY_WRITERS: RefCell<Vec<Rc<Writer>>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageArea {
+    Local,
+    Session,
+}
+
+impl StorageArea {
+    fn storage(&self) -> Option<Storage> {
+        let storage = WINDOW.with(|window| match self {
+            StorageArea::Local => window.local_storage(),
+            StorageArea::Session => window.session_storage(),
+        });
+        match storage {
+            Ok(storage) => storage,
+            Err(e) => {
+                log::error!("Error on getting {self:?} storage: {e:?}");
+                None
+            }
+        }
+    }
+}
+
+/// A state field that is loaded from Web Storage when it is created. Changes made through
+/// `Persisted::edit` or `Persisted::set` are written back once, at the end of the callback
+/// cycle in which they are made.
+pub struct Persisted<T> {
+    // Shared with the writer while a change is pending, so it is only serialized when written
+    value: Rc<T>,
+    writer: Rc<Writer>,
+    _sync_listener: Option<ListenerHandle>,
+}
+
+/// Marks the value of a `Persisted` as changed when it is dropped, if it was mutably accessed.
+pub struct PersistedMut<'a, T>
+where
+    T: Serialize + 'static,
+{
+    persisted: &'a mut Persisted<T>,
+    changed: bool,
+    was_pending: bool,
+}
+
+struct Writer {
+    area: StorageArea,
+    key: String,
+    debounce_ms: Cell<Option<i32>>,
+    pending: RefCell<Option<Rc<dyn ToJson>>>,
+    dirty: Cell<bool>,
+    timeout_id: Cell<Option<i32>>,
+    closure: OnceCell<Closure<dyn FnMut()>>,
+}
+
+impl<T> Persisted<T>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    /// Load the value from `localStorage`, or use `T::default()` if there is no valid value.
+    pub fn local(key: &str) -> Self {
+        Self::load(StorageArea::Local, key)
+    }
+
+    /// Load the value from `sessionStorage`, or use `T::default()` if there is no valid value.
+    pub fn session(key: &str) -> Self {
+        Self::load(StorageArea::Session, key)
+    }
+
+    fn load(area: StorageArea, key: &str) -> Self {
+        let value = area
+            .storage()
+            .and_then(|storage| storage.get_item(key).ok().flatten())
+            .and_then(|json| {
+                serde_json::from_str(&json)
+                    .inspect_err(|e| log::error!("Error on loading `{key}` from storage: {e}"))
+                    .ok()
+            })
+            .unwrap_or_default();
+        Self {
+            value: Rc::new(value),
+            writer: Rc::new(Writer {
+                area,
+                key: key.to_string(),
+                debounce_ms: Cell::new(None),
+                pending: RefCell::new(None),
+                dirty: Cell::new(false),
+                timeout_id: Cell::new(None),
+                closure: OnceCell::new(),
+            }),
+            _sync_listener: None,
+        }
+    }
+}
+
+impl<T> Persisted<T>
+where
+    T: Serialize + DeserializeOwned + 'static,
+{
+    /// Only write the value when it has not been changed for `ms` milliseconds. The last
+    /// change is always written when the `Persisted` is dropped.
+    pub fn with_debounce(self, ms: i32) -> Self {
+        self.writer.debounce_ms.set(Some(ms));
+        self
+    }
+
+    /// Update the value when another tab changes the same key. `field` returns this
+    /// `Persisted` from the component's state, the change is executed as a callback.
+    pub fn sync<C>(mut self, comp: &Comp<C>, field: fn(&mut C) -> &mut Persisted<T>) -> Self
+    where
+        C: Component + 'static,
+    {
+        let key = self.writer.key.clone();
+        let area = self.writer.area;
+        let handle = comp.listen_window("storage", move |state: &mut C, event: StorageEvent| {
+            if event.key().as_deref() != Some(key.as_str()) || !is_same_area(area, &event) {
+                return ShouldRender::No;
+            }
+            let value = match event.new_value() {
+                Some(json) => match serde_json::from_str(&json) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        log::error!("Error on loading `{key}` from a storage event: {e}");
+                        return ShouldRender::No;
+                    }
+                },
+                None => return ShouldRender::No,
+            };
+            field(state).value = Rc::new(value);
+            ShouldRender::Yes
+        });
+        self._sync_listener = Some(handle);
+        self
+    }
+
+    /// Mutable access to the value. It is written to storage at the end of the current
+    /// callback cycle, only if the guard was dereferenced mutably.
+    pub fn edit(&mut self) -> PersistedMut<'_, T> {
+        // Take back the pending value so it is not shared while being edited, it is put back
+        // when the guard is dropped
+        let was_pending = self.writer.pending.take().is_some();
+        PersistedMut {
+            persisted: self,
+            changed: false,
+            was_pending,
+        }
+    }
+
+    pub fn set(&mut self, value: T) {
+        *self.edit() = value;
+    }
+}
+
+fn is_same_area(area: StorageArea, event: &StorageEvent) -> bool {
+    match (event.storage_area(), area.storage()) {
+        (Some(event_storage), Some(storage)) => event_storage == storage,
+        _ => false,
+    }
+}
+
+impl<T> Deref for Persisted<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> Deref for PersistedMut<'_, T>
+where
+    T: Serialize + 'static,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.persisted.value
+    }
+}
+
+impl<T> DerefMut for PersistedMut<'_, T>
+where
+    T: Serialize + 'static,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.changed = true;
+        // The writer's reference is taken back by `Persisted::edit`
+        Rc::get_mut(&mut self.persisted.value)
+            .expect_throw("Internal error: a persisted value is shared while being edited")
+    }
+}
+
+impl<T> Drop for PersistedMut<'_, T>
+where
+    T: Serialize + 'static,
+{
+    fn drop(&mut self) {
+        let value: Rc<dyn ToJson> = self.persisted.value.clone();
+        if self.changed {
+            self.persisted.writer.mark_dirty(value);
+        } else if self.was_pending {
+            *self.persisted.writer.pending.borrow_mut() = Some(value);
+        }
+    }
+}
+
+trait ToJson {
+    fn to_json(&self) -> serde_json::Result<String>;
+}
+
+impl<T: Serialize> ToJson for T {
+    fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
+
+/// Write the values that were changed in the callback cycle that is ending.
+pub(crate) fn write_dirty_values() {
+    let writers = DIRTY_WRITERS.with(|writers| std::mem::take(&mut *writers.borrow_mut()));
+    for writer in writers {
+        writer.dirty.set(false);
+        writer.write();
+    }
+}
+
+impl Writer {
+    fn mark_dirty(self: &Rc<Self>, value: Rc<dyn ToJson>) {
+        *self.pending.borrow_mut() = Some(value);
+        if !is_update_queue_executing() {
+            self.write();
+            return;
+        }
+        if !self.dirty.replace(true) {
+            DIRTY_WRITERS.with(|writers| writers.borrow_mut().push(Rc::clone(self)));
+        }
+    }
+
+    fn write(self: &Rc<Self>) {
+        let Some(ms) = self.debounce_ms.get() else {
+            self.flush();
+            return;
+        };
+        self.clear_timeout();
+        let closure = self.closure.get_or_init(|| {
+            let writer: Weak<Writer> = Rc::downgrade(self);
+            Closure::new(move || {
+                if let Some(writer) = writer.upgrade() {
+                    writer.timeout_id.set(None);
+                    writer.flush();
+                }
+            })
+        });
+        let id = WINDOW.with(|window| {
+            window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                ms,
+            )
+        });
+        match id {
+            Ok(id) => self.timeout_id.set(Some(id)),
+            Err(e) => {
+                log::error!("Error on setting a timeout to write `{}`: {e:?}", self.key);
+                self.flush();
+            }
+        }
+    }
+
+    fn flush(&self) {
+        let pending = self.pending.take();
+        let Some(value) = pending else {
+            return;
+        };
+        match value.to_json() {
+            Ok(json) => self.write_now(&json),
+            Err(e) => log::error!("Error on serializing `{}`: {e}", self.key),
+        }
+    }
+
+    fn write_now(&self, json: &str) {
+        let Some(storage) = self.area.storage() else {
+            return;
+        };
+        if let Err(e) = storage.set_item(&self.key, json) {
+            log::error!("Error on writing `{}` to storage: {e:?}", self.key);
+        }
+    }
+
+    fn clear_timeout(&self) {
+        if let Some(id) = self.timeout_id.take() {
+            WINDOW.with(|window| window.clear_timeout_with_handle(id));
+        }
+    }
+}
+
+impl Drop for Writer {
+    fn drop(&mut self) {
+        self.clear_timeout();
+        self.flush();
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::{Persisted, StorageArea};
+    use crate::{
+        WsElement,
+        component::{Component, ComponentViewState, Context, RcComp},
+        dom::Element,
+    };
+
+    const KEY: &str = "spair-test-persisted";
+
+    struct TestViewState(Element);
+
+    impl ComponentViewState for TestViewState {
+        fn root_element(&self) -> &Element {
+            &self.0
+        }
+    }
+
+    struct Numbers {
+        numbers: Persisted<Vec<u32>>,
+        stored_in_callback: Option<String>,
+    }
+
+    impl Component for Numbers {
+        type ViewState = TestViewState;
+        fn create(_ccontext: &Context<Self>) -> Self::ViewState {
+            TestViewState(WsElement::create_element("span").create_element_with_capacity(0))
+        }
+        fn update(_view_state: &mut Self::ViewState, _ucontext: &Context<Self>) {}
+    }
+
+    fn stored() -> Option<String> {
+        StorageArea::Session
+            .storage()
+            .and_then(|storage| storage.get_item(KEY).ok().flatten())
+    }
+
+    #[wasm_bindgen_test]
+    fn write_once_at_the_end_of_the_callback() {
+        if let Some(storage) = StorageArea::Session.storage() {
+            storage.remove_item(KEY).unwrap();
+        }
+        let rc_comp = RcComp::new(|_| Numbers {
+            numbers: Persisted::session(KEY),
+            stored_in_callback: None,
+        });
+        let comp = rc_comp.comp();
+
+        comp.callback(|state| {
+            state.numbers.edit().push(1);
+            state.numbers.edit().push(2);
+            // Not changed, the pending value is kept
+            assert_eq!(state.numbers.edit().len(), 2);
+            state.stored_in_callback = stored();
+        })
+        .call();
+        assert_eq!(
+            comp.with_state(|state| state.stored_in_callback.clone()),
+            Some(None)
+        );
+        assert_eq!(stored().as_deref(), Some("[1,2]"));
+
+        StorageArea::Session
+            .storage()
+            .unwrap()
+            .remove_item(KEY)
+            .unwrap();
+        comp.callback(|state| assert_eq!(state.numbers.edit().len(), 2))
+            .call();
+        assert_eq!(stored(), None);
+    }
+}
@@ -0,0 +1,171 @@
+//! Counters of callbacks, updates and DOM writes for each component type. They are only
+//! recorded with the `profiling` feature, without it the recording functions are empty.
+
+#[cfg(feature = "profiling")]
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+};
+
+#[cfg(feature = "profiling")]
+use wasm_bindgen::JsValue;
+
+/// Statistics of a component type, components are identified by their type names.
+#[cfg(feature = "profiling")]
+#[derive(Debug, Clone, Default)]
+pub struct ComponentStats {
+    pub name: &'static str,
+    /// Callbacks that were executed on the component's state.
+    pub callbacks: u64,
+    /// Calls to `Component::update` (and `update_queued`), including the first update.
+    pub updates: u64,
+    /// Total time spent in updates, in milliseconds. This is the self time: updates of child
+    /// components that are done while updating this component are not counted here.
+    pub update_time_ms: f64,
+    /// DOM writes (attributes, classes, texts, inserted/removed nodes...) done in updates,
+    /// excluding the ones done by updates of child components.
+    pub dom_writes: u64,
+}
+
+#[cfg(feature = "profiling")]
+thread_local! {
+    static STATS: RefCell<BTreeMap<&'static str, ComponentStats>> = const { RefCell::new(BTreeMap::new()) };
+    static DOM_WRITES: Cell<u64> = const { Cell::new(0) };
+    // Time and DOM writes of the updates nested in the current update
+    static NESTED: Cell<(f64, u64)> = const { Cell::new((0.0, 0)) };
+}
+
+#[cfg(feature = "profiling")]
+fn with_stats<C>(f: impl FnOnce(&mut ComponentStats)) {
+    let name = std::any::type_name::<C>();
+    STATS.with(|stats| {
+        let mut stats = stats.borrow_mut();
+        let entry = stats.entry(name).or_insert_with(|| ComponentStats {
+            name,
+            ..Default::default()
+        });
+        f(entry);
+    });
+}
+
+#[cfg(feature = "profiling")]
+fn now() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map(|performance| performance.now())
+        .unwrap_or_default()
+}
+
+/// The statistics of all components that have been recorded so far, ordered by name.
+#[cfg(feature = "profiling")]
+pub fn snapshot() -> Vec<ComponentStats> {
+    STATS.with(|stats| stats.borrow().values().cloned().collect())
+}
+
+/// Clear all recorded statistics.
+#[cfg(feature = "profiling")]
+pub fn reset() {
+    STATS.with(|stats| stats.borrow_mut().clear());
+}
+
+/// Print the current snapshot to the browser console with `console.table`.
+#[cfg(feature = "profiling")]
+pub fn print_table() {
+    let rows = js_sys::Array::new();
+    for stats in snapshot() {
+        let row = js_sys::Object::new();
+        let fields = [
+            ("component", JsValue::from_str(stats.name)),
+            ("callbacks", JsValue::from_f64(stats.callbacks as f64)),
+            ("updates", JsValue::from_f64(stats.updates as f64)),
+            ("update time (ms)", JsValue::from_f64(stats.update_time_ms)),
+            ("dom writes", JsValue::from_f64(stats.dom_writes as f64)),
+        ];
+        for (key, value) in fields {
+            if let Err(e) = js_sys::Reflect::set(&row, &JsValue::from_str(key), &value) {
+                log::error!("Error on building a profiler row: {e:?}");
+            }
+        }
+        rows.push(&row);
+    }
+    web_sys::console::table_1(&rows);
+}
+
+#[inline(always)]
+#[cfg_attr(
+    not(feature = "profiling"),
+    allow(clippy::extra_unused_type_parameters)
+)]
+pub(crate) fn record_callback<C>() {
+    #[cfg(feature = "profiling")]
+    with_stats::<C>(|stats| stats.callbacks += 1);
+}
+
+#[inline(always)]
+pub(crate) fn record_dom_write() {
+    #[cfg(feature = "profiling")]
+    DOM_WRITES.with(|count| count.set(count.get() + 1));
+}
+
+/// Execute an update of a component of type `C`, its duration and DOM writes are recorded.
+#[inline(always)]
+#[cfg_attr(
+    not(feature = "profiling"),
+    allow(clippy::extra_unused_type_parameters)
+)]
+pub(crate) fn profile_update<C, R>(update: impl FnOnce() -> R) -> R {
+    #[cfg(feature = "profiling")]
+    {
+        let outer_nested = NESTED.with(|nested| nested.replace((0.0, 0)));
+        let dom_writes = DOM_WRITES.with(|count| count.get());
+        let start = now();
+        let output = update();
+        let duration = now() - start;
+        let dom_writes = DOM_WRITES.with(|count| count.get()) - dom_writes;
+        let (nested_duration, nested_dom_writes) = NESTED.with(|nested| {
+            nested.replace((outer_nested.0 + duration, outer_nested.1 + dom_writes))
+        });
+        with_stats::<C>(|stats| {
+            stats.updates += 1;
+            stats.update_time_ms += duration - nested_duration;
+            stats.dom_writes += dom_writes - nested_dom_writes;
+        });
+        output
+    }
+    #[cfg(not(feature = "profiling"))]
+    update()
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "profiling"))]
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::{profile_update, record_dom_write, snapshot};
+
+    struct Parent;
+    struct Child;
+
+    fn dom_writes<C>() -> u64 {
+        let name = std::any::type_name::<C>();
+        snapshot()
+            .into_iter()
+            .find(|stats| stats.name == name)
+            .map(|stats| stats.dom_writes)
+            .unwrap_or_default()
+    }
+
+    #[wasm_bindgen_test]
+    fn nested_updates_are_not_counted_in_parent() {
+        profile_update::<Parent, _>(|| {
+            record_dom_write();
+            profile_update::<Child, _>(|| {
+                record_dom_write();
+                record_dom_write();
+            });
+            record_dom_write();
+        });
+        assert_eq!(dom_writes::<Parent>(), 2);
+        assert_eq!(dom_writes::<Child>(), 2);
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt::Display;
+
+use crate::routing::{decode, percent_encode};
+
+/// A query string (or a fragment in the same format) like `page=2&tags=a&tags=b`. Keys and
+/// values are percent-decoded when parsing and percent-encoded when formatting.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    pairs: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    Missing { key: String },
+    Invalid { key: String, value: String },
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::Missing { key } => write!(f, "missing query parameter `{key}`"),
+            QueryError::Invalid { key, value } => {
+                write!(f, "invalid value for query parameter `{key}`: `{value}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// A type that can be parsed from a query string. Implemented by `#[derive(Query)]` for a
+/// struct: fields of type `Option<T>` are optional, fields of type `Vec<T>` take all values
+/// of a repeated key, other fields are required.
+pub trait FromQuery: Sized {
+    fn from_query(query: &Query) -> Result<Self, QueryError>;
+
+    fn from_query_str(query: &str) -> Result<Self, QueryError> {
+        Self::from_query(&Query::parse(query))
+    }
+}
+
+/// A type that can be formatted as a query string. Implemented by `#[derive(Query)]`.
+pub trait ToQuery {
+    fn to_query(&self, query: &mut Query);
+
+    /// The encoded query string, without the leading `?`.
+    fn to_query_string(&self) -> String {
+        let mut query = Query::default();
+        self.to_query(&mut query);
+        query.to_string()
+    }
+}
+
+/// The value of a query parameter.
+pub trait QueryValue: Sized {
+    fn parse_value(value: &str) -> Option<Self>;
+    fn format_value(&self) -> String;
+}
+
+macro_rules! impl_query_value {
+    ($($type:ty)+) => {$(
+        impl QueryValue for $type {
+            fn parse_value(value: &str) -> Option<Self> {
+                value.parse().ok()
+            }
+
+            fn format_value(&self) -> String {
+                self.to_string()
+            }
+        }
+    )+};
+}
+
+impl_query_value! {
+    String bool char i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize f32 f64
+}
+
+impl Query {
+    /// Parse a query string, a leading `?` or `#` is ignored.
+    pub fn parse(query: &str) -> Self {
+        let query = query
+            .strip_prefix('?')
+            .or_else(|| query.strip_prefix('#'))
+            .unwrap_or(query);
+        let pairs = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (percent_decode(key), percent_decode(value))
+            })
+            .collect();
+        Self { pairs }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// The first value of `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> {
+        self.pairs
+            .iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn required<T: QueryValue>(&self, key: &str) -> Result<T, QueryError> {
+        match self.get(key) {
+            Some(value) => parse_value(key, value),
+            None => Err(QueryError::Missing {
+                key: key.to_string(),
+            }),
+        }
+    }
+
+    /// An empty value is the same as a missing key.
+    pub fn optional<T: QueryValue>(&self, key: &str) -> Result<Option<T>, QueryError> {
+        match self.get(key) {
+            Some(value) if !value.is_empty() => parse_value(key, value).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// All values of a repeated key.
+    pub fn all<T: QueryValue>(&self, key: &str) -> Result<Vec<T>, QueryError> {
+        self.get_all(key)
+            .map(|value| parse_value(key, value))
+            .collect()
+    }
+
+    pub fn push(&mut self, key: &str, value: &impl QueryValue) {
+        self.pairs.push((key.to_string(), value.format_value()));
+    }
+}
+
+fn parse_value<T: QueryValue>(key: &str, value: &str) -> Result<T, QueryError> {
+    T::parse_value(value).ok_or_else(|| QueryError::Invalid {
+        key: key.to_string(),
+        value: value.to_string(),
+    })
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (key, value)) in self.pairs.iter().enumerate() {
+            if index > 0 {
+                f.write_str("&")?;
+            }
+            write!(f, "{}={}", percent_encode(key), percent_encode(value))?;
+        }
+        Ok(())
+    }
+}
+
+/// Decode `%XX` sequences and `+` as a space. An invalid sequence is kept as is.
+pub fn percent_decode(value: &str) -> String {
+    decode(value, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Query, QueryError, percent_decode};
+    use crate::routing::percent_encode;
+
+    #[test]
+    fn percent_decoding() {
+        assert_eq!(percent_decode("a%20b+c%26%C3%A9"), "a b c&é");
+        let value = "tags & more?=#/";
+        assert_eq!(percent_decode(&percent_encode(value)), value);
+    }
+
+    #[test]
+    fn typed_values() {
+        let query = Query::parse("?page=2&tags=a&tags=b%20c&q=&flag=true");
+        assert_eq!(query.required::<u32>("page"), Ok(2));
+        assert_eq!(
+            query.all::<String>("tags"),
+            Ok(vec!["a".to_string(), "b c".to_string()])
+        );
+        assert_eq!(query.optional::<String>("q"), Ok(None));
+        assert_eq!(query.optional::<u32>("size"), Ok(None));
+        assert_eq!(query.required::<bool>("flag"), Ok(true));
+        assert!(query.all::<u32>("missing").is_ok_and(|v| v.is_empty()));
+    }
+
+    #[test]
+    fn errors() {
+        let query = Query::parse("page=two");
+        assert_eq!(
+            query.required::<u32>("page"),
+            Err(QueryError::Invalid {
+                key: "page".to_string(),
+                value: "two".to_string()
+            })
+        );
+        assert_eq!(
+            query.required::<u32>("size"),
+            Err(QueryError::Missing {
+                key: "size".to_string()
+            })
+        );
+        assert!(query.all::<u32>("page").is_err());
+    }
+
+    #[test]
+    fn format_query() {
+        let mut query = Query::default();
+        query.push("page", &2);
+        query.push("tags", &"a b".to_string());
+        query.push("tags", &"c&d".to_string());
+        let query_string = query.to_string();
+        assert_eq!(query_string, "page=2&tags=a%20b&tags=c%26d");
+        assert_eq!(Query::parse(&query_string), query);
+    }
+}
@@ -0,0 +1,74 @@
+use std::{cell::Cell, ops::Deref};
+
+/// A state field that is rendered by queue render.
+///
+/// List the field in `#[queued(...)]` on `fn update` of an `#[impl_component]`. When a
+/// callback changes a `Queued` field and returns `ShouldRender::No`, only the pieces of
+/// the view that read the field are updated, instead of running the whole `fn update`.
+pub struct Queued<T> {
+    value: T,
+    changed: Cell<bool>,
+}
+
+impl<T> Queued<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            changed: Cell::new(false),
+        }
+    }
+
+    pub fn set(&mut self, value: T) {
+        self.value = value;
+        self.changed.set(true);
+    }
+
+    /// Get a mutable reference to the value. The value is always considered as changed.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.changed.set(true);
+        &mut self.value
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    #[doc(hidden)]
+    pub fn take_changed(&self) -> bool {
+        self.changed.replace(false)
+    }
+
+    #[doc(hidden)]
+    pub fn mark_rendered(&self) {
+        self.changed.set(false);
+    }
+}
+
+impl<T: PartialEq> Queued<T> {
+    /// Only mark the value as changed if the new value is different from the current value.
+    pub fn set_if_changed(&mut self, value: T) {
+        if self.value != value {
+            self.set(value);
+        }
+    }
+}
+
+impl<T: Default> Default for Queued<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> Deref for Queued<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> From<T> for Queued<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
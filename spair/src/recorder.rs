@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::component::{Comp, Component, ShouldRender};
+
+/// A component whose state can be recorded after every callback, then rewound to one of
+/// the recorded snapshots for debugging.
+pub trait RecordState: Component + Serialize + DeserializeOwned + 'static {
+    /// Put a recorded state back. The default replaces the whole state, override it to keep
+    /// the fields that are skipped by serde, such as a `Comp<Self>`.
+    fn restore(&mut self, recorded: Self) {
+        *self = recorded;
+    }
+}
+
+/// Snapshots of a component's state, the first one is the state when the recording started.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub component: String,
+    pub snapshots: Vec<Value>,
+}
+
+pub(crate) struct Recorder<C> {
+    snapshot: fn(&C) -> serde_json::Result<Value>,
+    snapshots: Vec<Value>,
+    // A rewind is executed as a callback, but its result must not be recorded
+    skip_next: bool,
+}
+
+impl<C> Recorder<C> {
+    pub fn record(&mut self, state: &C) {
+        if std::mem::take(&mut self.skip_next) {
+            return;
+        }
+        match (self.snapshot)(state) {
+            Ok(snapshot) => self.snapshots.push(snapshot),
+            Err(e) => log::error!(
+                "Error on recording a snapshot of `{}`: {e}",
+                std::any::type_name::<C>()
+            ),
+        }
+    }
+
+    fn to_recording(&self) -> Recording {
+        Recording {
+            component: std::any::type_name::<C>().to_string(),
+            snapshots: self.snapshots.clone(),
+        }
+    }
+}
+
+impl<C> Comp<C>
+where
+    C: RecordState,
+{
+    /// Start recording a snapshot of the state after every callback. Snapshots of a previous
+    /// recording are cleared.
+    pub fn start_recording(&self) {
+        self.start_recording_with(Vec::new());
+        // Record the current state as the first snapshot
+        self.callback(|_| ShouldRender::No).call();
+    }
+
+    fn start_recording_with(&self, snapshots: Vec<Value>) {
+        self.with_recorder(|recorder| {
+            *recorder = Some(Recorder {
+                snapshot: |state| serde_json::to_value(state),
+                snapshots,
+                skip_next: false,
+            })
+        });
+    }
+
+    /// Stop recording, return the recorded snapshots.
+    pub fn stop_recording(&self) -> Option<Recording> {
+        self.with_recorder(|recorder| recorder.take().map(|recorder| recorder.to_recording()))
+            .flatten()
+    }
+
+    /// The snapshots recorded so far.
+    pub fn recording(&self) -> Option<Recording> {
+        self.with_recorder(|recorder| recorder.as_ref().map(Recorder::to_recording))
+            .flatten()
+    }
+
+    /// The snapshots recorded so far, as JSON, to be attached to a bug report.
+    pub fn export_recording(&self) -> Option<String> {
+        let recording = self.recording()?;
+        serde_json::to_string(&recording)
+            .inspect_err(|e| log::error!("Error on exporting a recording: {e}"))
+            .ok()
+    }
+
+    /// Replace the current recording with an exported one, then `rewind` to replay it. New
+    /// snapshots are appended to the loaded ones.
+    pub fn load_recording(&self, json: &str) -> serde_json::Result<()> {
+        let recording: Recording = serde_json::from_str(json)?;
+        self.start_recording_with(recording.snapshots);
+        Ok(())
+    }
+
+    /// Restore the state from the snapshot at `index` and update the view. This is executed
+    /// like a callback, but its result is not recorded.
+    pub fn rewind(&self, index: usize) {
+        let snapshot = self
+            .with_recorder(|recorder| {
+                recorder
+                    .as_ref()
+                    .and_then(|recorder| recorder.snapshots.get(index).cloned())
+            })
+            .flatten();
+        let Some(snapshot) = snapshot else {
+            log::error!("No snapshot at index {index} to rewind to");
+            return;
+        };
+        let comp = self.clone();
+        self.callback(move |state| {
+            comp.with_recorder(|recorder| {
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.skip_next = true;
+                }
+            });
+            match serde_json::from_value(snapshot.clone()) {
+                Ok(recorded) => {
+                    state.restore(recorded);
+                    ShouldRender::Yes
+                }
+                Err(e) => {
+                    log::error!("Error on restoring the snapshot at index {index}: {e}");
+                    ShouldRender::No
+                }
+            }
+        })
+        .call();
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::RecordState;
+    use crate::{
+        WsElement,
+        component::{Component, ComponentViewState, Context, RcComp},
+        dom::Element,
+    };
+
+    struct TestViewState(Element);
+
+    impl ComponentViewState for TestViewState {
+        fn root_element(&self) -> &Element {
+            &self.0
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Todo {
+        items: Vec<String>,
+        done: u32,
+    }
+
+    impl Component for Todo {
+        type ViewState = TestViewState;
+        fn create(_ccontext: &Context<Self>) -> Self::ViewState {
+            TestViewState(WsElement::create_element("span").create_element_with_capacity(0))
+        }
+        fn update(_view_state: &mut Self::ViewState, _ucontext: &Context<Self>) {}
+    }
+
+    impl RecordState for Todo {}
+
+    fn new_todo() -> RcComp<Todo> {
+        RcComp::new(|_| Todo {
+            items: Vec::new(),
+            done: 0,
+        })
+    }
+
+    fn state_of(rc_comp: &RcComp<Todo>) -> (Vec<String>, u32) {
+        rc_comp
+            .comp()
+            .with_state(|state| (state.items.clone(), state.done))
+            .unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn replay_an_exported_recording() {
+        let recorded = new_todo();
+        let comp = recorded.comp();
+        comp.start_recording();
+        let mut expected = vec![state_of(&recorded)];
+        comp.callback(|state| state.items.push("a".to_string()))
+            .call();
+        expected.push(state_of(&recorded));
+        comp.callback(|state| state.items.push("b".to_string()))
+            .call();
+        expected.push(state_of(&recorded));
+        comp.callback(|state| state.done += 1).call();
+        expected.push(state_of(&recorded));
+        let json = comp.export_recording().unwrap();
+
+        let replayed = new_todo();
+        let comp = replayed.comp();
+        comp.load_recording(&json).unwrap();
+        for (index, expected) in expected.iter().enumerate() {
+            comp.rewind(index);
+            assert_eq!(&state_of(&replayed), expected);
+        }
+        // Rewinding does not add snapshots
+        assert_eq!(comp.recording().unwrap().snapshots.len(), expected.len());
+    }
+}
@@ -0,0 +1,122 @@
+use std::{cell::Cell, future::Future, pin::Pin};
+
+use crate::component::{Comp, Component, ShouldRender, SpairSpawnLocalWithCallback};
+
+thread_local! {
+    static NEXT_REQUEST_ID: Cell<u64> = const { Cell::new(1) };
+}
+
+fn next_request_id() -> u64 {
+    NEXT_REQUEST_ID.with(|id| {
+        let value = id.get();
+        id.set(value + 1);
+        value
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceState<T, E> {
+    Idle,
+    Loading,
+    Ready(T),
+    Failed(E),
+}
+
+type ResourceFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>>>>;
+type Loader<T, E> = Box<dyn Fn() -> ResourceFuture<T, E>>;
+type Spawner<T, E> = Box<dyn Fn(u64, ResourceFuture<T, E>)>;
+
+/// A state field for data that is loaded asynchronously. Only the response of the latest
+/// request is kept, responses of earlier requests are ignored.
+pub struct Resource<T, E> {
+    state: ResourceState<T, E>,
+    request_id: Option<u64>,
+    loader: Loader<T, E>,
+    spawner: Spawner<T, E>,
+}
+
+impl<T, E> Resource<T, E>
+where
+    T: 'static,
+    E: 'static,
+{
+    /// Create an idle resource. `field` returns this resource from the component's state,
+    /// `loader` creates the future for every request.
+    pub fn new<C, F>(
+        comp: &Comp<C>,
+        field: fn(&mut C) -> &mut Resource<T, E>,
+        loader: impl Fn() -> F + 'static,
+    ) -> Self
+    where
+        C: Component + 'static,
+        F: Future<Output = Result<T, E>> + 'static,
+    {
+        let callback =
+            comp.callback_arg(move |state: &mut C, (id, result): (u64, Result<T, E>)| {
+                field(state).set_result(id, result)
+            });
+        Self {
+            state: ResourceState::Idle,
+            request_id: None,
+            loader: Box::new(move || Box::pin(loader())),
+            spawner: Box::new(move |id, future| {
+                let callback = callback.clone();
+                async move { (id, future.await) }.spawn_local_with_callback(callback);
+            }),
+        }
+    }
+
+    pub fn state(&self) -> &ResourceState<T, E> {
+        &self.state
+    }
+
+    pub fn is_loading(&self) -> bool {
+        matches!(self.state, ResourceState::Loading)
+    }
+
+    pub fn ready(&self) -> Option<&T> {
+        match &self.state {
+            ResourceState::Ready(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Start a request if the resource is idle or has failed.
+    pub fn fetch(&mut self) {
+        if matches!(self.state, ResourceState::Idle | ResourceState::Failed(_)) {
+            self.refetch();
+        }
+    }
+
+    /// Start a new request, the response of a pending request will be ignored.
+    pub fn refetch(&mut self) {
+        let future = (self.loader)();
+        self.load(future);
+    }
+
+    /// Same as `refetch`, but with a future other than the one from the loader.
+    pub fn load(&mut self, future: impl Future<Output = Result<T, E>> + 'static) {
+        let id = next_request_id();
+        self.request_id = Some(id);
+        self.state = ResourceState::Loading;
+        (self.spawner)(id, Box::pin(future));
+    }
+
+    /// Back to `ResourceState::Idle`, the response of a pending request will be ignored.
+    pub fn invalidate(&mut self) {
+        self.request_id = None;
+        self.state = ResourceState::Idle;
+    }
+
+    fn set_result(&mut self, id: u64, result: Result<T, E>) -> ShouldRender {
+        if self.request_id != Some(id) {
+            return ShouldRender::No;
+        }
+        self.request_id = None;
+        self.state = match result {
+            Ok(value) => ResourceState::Ready(value),
+            Err(e) => ResourceState::Failed(e),
+        };
+        ShouldRender::Yes
+    }
+}
@@ -1,16 +1,22 @@
 use std::{
-    cell::{OnceCell, RefCell},
-    rc::Rc,
+    cell::{Cell, OnceCell, RefCell},
+    rc::{Rc, Weak},
 };
 
 use wasm_bindgen::{JsCast, JsValue, UnwrapThrowExt, prelude::Closure};
-use web_sys::{HtmlAnchorElement, HtmlAreaElement, Location, MouseEvent};
+use web_sys::{
+    BeforeUnloadEvent, HtmlAnchorElement, HtmlAreaElement, Location, MouseEvent, PopStateEvent,
+};
 
 use crate::{
-    component::{Comp, Component},
+    component::{
+        Comp, Component, execute_then_the_update_queue, is_update_queue_executing,
+        put_callback_on_update_queue,
+    },
     dom::WsElement,
     events::EventListener,
     helper,
+    listener::ListenerHandle,
 };
 
 pub trait Route: Sized {
@@ -25,25 +31,398 @@ impl Route for () {
     }
 }
 
+/// A route that can be parsed from a URL without changing the current location, it is
+/// required by `Comp::guard_navigation` to give the guard the next route. Implemented by
+/// `#[derive(Route)]`.
+pub trait RouteFromUrl: Route {
+    /// Parse the route from a URL, it may be relative to the current location.
+    fn from_url(url: &str) -> Self;
+}
+
+/// Split `url` into its path, query string and fragment (e.g. `("/search", "?q=a", "#top")`),
+/// a relative `url` is resolved against the current location. Used by `#[derive(Route)]`.
+#[doc(hidden)]
+pub fn url_parts(url: &str) -> (String, String, String) {
+    let base = get_current_location().href().unwrap_or_default();
+    match web_sys::Url::new_with_base(url, &base) {
+        Ok(url) => (url.pathname(), url.search(), url.hash()),
+        Err(e) => {
+            log::error!("Error on parsing the url `{url}`: {e:?}");
+            Default::default()
+        }
+    }
+}
+
+/// Split a path into its non-empty segments, used by `#[derive(Route)]`.
+#[doc(hidden)]
+pub fn route_segments(path: &str) -> Vec<&str> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+/// Split a path like `/search?q=a#top` into `("/search", "?q=a", "#top")`, used by
+/// `#[derive(Route)]`.
+#[doc(hidden)]
+pub fn split_route_path(path: &str) -> (&str, &str, &str) {
+    let (path, hash) = path.split_at(path.find('#').unwrap_or(path.len()));
+    let (path, search) = path.split_at(path.find('?').unwrap_or(path.len()));
+    (path, search, hash)
+}
+
+/// A route of a child component, parsed from the rest of the URL that a route of its parent
+/// delegates to it with a `*rest` parameter. Implemented by `#[derive(Route)]`.
+pub trait NestedRoute: Sized {
+    fn from_path(path: &str) -> Self;
+    /// The path of this route, relative to the parent route.
+    fn path(&self) -> String;
+}
+
+impl NestedRoute for String {
+    fn from_path(path: &str) -> Self {
+        path.to_string()
+    }
+
+    fn path(&self) -> String {
+        self.clone()
+    }
+}
+
+/// Append the path of a nested route to the url of its parent route, used by
+/// `#[derive(Route)]`.
+#[doc(hidden)]
+pub fn nested_route_url(parent_url: String, nested_path: &str) -> String {
+    let nested_path = nested_path.trim_start_matches('/');
+    match (parent_url.as_str(), nested_path.is_empty()) {
+        ("", true) => "/".to_string(),
+        (_, true) => parent_url,
+        // Only a query string or a fragment
+        _ if nested_path.starts_with(['?', '#']) => parent_url + nested_path,
+        _ => format!("{}/{nested_path}", parent_url.trim_end_matches('/')),
+    }
+}
+
+/// Encode every byte except `A-Z a-z 0-9 - . _ ~`, used for the parameters of a route by
+/// `#[derive(Route)]`, and for query strings.
+pub fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(char::from(byte))
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Decode `%XX` sequences in a path segment, `+` is kept. An invalid sequence is kept as is.
+/// Used by `#[derive(Route)]`.
+#[doc(hidden)]
+pub fn percent_decode_path(value: &str) -> String {
+    decode(value, false)
+}
+
+pub(crate) fn decode(value: &str, plus_as_space: bool) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' if plus_as_space => decoded.push(b' '),
+            b'%' => {
+                let hex = bytes
+                    .get(index + 1..index + 3)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match hex {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        index += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+type RouterFn = dyn Fn(&Location);
+
+/// A router registered by a component, it is removed when the component is unmounted.
+pub(crate) struct Router {
+    _router: Rc<RouterFn>,
+}
+
+/// Register a router of a component at `depth` in the component tree. Routers are kept in
+/// the order of their depths, a child component may register its router before its parent,
+/// e.g. when the child is created in the parent's `new_state`.
+fn register_router(depth: usize, router: impl Fn(&Location) + 'static) -> Router {
+    setup_routing_handlers();
+    let router: Rc<RouterFn> = Rc::new(router);
+    ROUTERS.with(|routers| {
+        let mut routers = routers.borrow_mut();
+        routers.retain(|(_, router)| router.strong_count() > 0);
+        let index = routers.partition_point(|(router_depth, _)| *router_depth <= depth);
+        routers.insert(index, (depth, Rc::downgrade(&router)));
+    });
+    Router { _router: router }
+}
+
+/// Notify all routers, parents are notified before their children.
+fn do_routing() {
+    let current_location = get_current_location();
+    let current_href = current_location.href().unwrap_throw();
+    if LAST_HREF.with(|last_href| current_href.as_str() == *last_href.borrow()) {
+        return;
+    }
+    LAST_HREF.set(current_href);
+    let routers: Vec<Rc<RouterFn>> = ROUTERS.with(|routers| {
+        routers
+            .borrow()
+            .iter()
+            .filter_map(|(_, router)| router.upgrade())
+            .collect()
+    });
+    for router in routers {
+        router(&current_location);
+    }
+}
+
 pub fn setup_routing<C: 'static + Component, R: 'static + Route>(
     set_route: impl Fn(&mut C, R) + 'static,
     comp: Comp<C>,
 ) {
-    LAST_HREF.set(get_current_location().href().unwrap_throw());
-
     let set_route = comp.callback_arg(set_route);
-    let do_routing = move || {
-        let current_location = get_current_location();
-        let current_href = current_location.href().unwrap_throw();
-        if LAST_HREF.with(|last_href| current_href.as_str() == *last_href.borrow()) {
+    let router = register_router(comp.depth(), move |location| {
+        set_route.call(R::from_location(location))
+    });
+    comp.keep_router(router);
+}
+
+impl<C> Comp<C>
+where
+    C: 'static + Component,
+{
+    /// Route the part of the URL that a route of an ancestor delegates to this component.
+    /// `nested` extracts this component's route from the ancestor's route `P`, `set_route` is
+    /// only called when the extracted route changes. Return the current route, or the route
+    /// for an empty path if `nested` returns `None`.
+    pub fn setup_nested_routing<P, R>(
+        &self,
+        nested: fn(P) -> Option<R>,
+        set_route: fn(&mut C, R),
+    ) -> R
+    where
+        P: Route + 'static,
+        R: NestedRoute + 'static,
+    {
+        let current =
+            nested(P::from_location(&get_current_location())).unwrap_or_else(|| R::from_path(""));
+        let last_path = RefCell::new(current.path());
+        let set_route = self.callback_arg(set_route);
+        let router = register_router(self.depth(), move |location| {
+            // The location is not for this component, it may be about to be removed
+            let Some(route) = nested(P::from_location(location)) else {
+                return;
+            };
+            let path = route.path();
+            if *last_path.borrow() == path {
+                return;
+            }
+            *last_path.borrow_mut() = path;
+            set_route.call(route);
+        });
+        self.keep_router(router);
+        current
+    }
+}
+
+/// Navigate to `route`, the same as clicking a link to it: a new entry is added to the
+/// browser's history, and the routers are notified.
+pub fn navigate(route: &impl Route) {
+    navigate_to_url(&route.url(), false);
+}
+
+/// Same as `navigate`, but the current entry of the browser's history is replaced.
+pub fn navigate_replace(route: &impl Route) {
+    navigate_to_url(&route.url(), true);
+}
+
+/// Go back to the previous entry of the browser's history, the routers are notified on the
+/// `popstate` event.
+pub fn back() {
+    with_history(|history| history.back(), "back");
+}
+
+/// Go forward to the next entry of the browser's history.
+pub fn forward() {
+    with_history(|history| history.forward(), "forward");
+}
+
+fn with_history(f: impl FnOnce(&web_sys::History) -> Result<(), JsValue>, action: &str) {
+    let history = match helper::WINDOW.with(|window| window.history()) {
+        Ok(history) => history,
+        Err(e) => {
+            log::error!("Error on getting window.history: {e:?}");
+            return;
+        }
+    };
+    if let Err(e) = f(&history) {
+        log::error!("Error on history.{action}: {e:?}");
+    }
+}
+
+fn navigate_to_url(url: &str, replace: bool) {
+    // Guards read the states of their components, so a navigation from a callback is done
+    // after the callback returns and its component's state is released.
+    if is_update_queue_executing() {
+        let url = url.to_string();
+        put_callback_on_update_queue(move || navigate_now(&url, replace));
+        return;
+    }
+    navigate_now(url, replace);
+}
+
+fn navigate_now(url: &str, replace: bool) {
+    if !allowed_by_guards(url) {
+        return;
+    }
+    // Each entry that is added by spair has its index in its state, see `handle_popstate`
+    let index = HISTORY_INDEX.get() + u32::from(!replace);
+    let state = JsValue::from(index);
+    with_history(
+        |history| match replace {
+            true => history.replace_state_with_url(&state, "", Some(url)),
+            false => history.push_state_with_url(&state, "", Some(url)),
+        },
+        if replace { "replaceState" } else { "pushState" },
+    );
+    HISTORY_INDEX.set(index);
+    // A `set_route` callback is queued if the navigation is done while the update queue is
+    // executing
+    execute_then_the_update_queue(do_routing);
+}
+
+type GuardFn = dyn Fn(&str) -> bool;
+
+/// A guard added by `Comp::guard_navigation`, it is removed when it is dropped.
+#[must_use = "the guard is removed when it is dropped"]
+pub struct NavigationGuard {
+    _guard: Rc<GuardFn>,
+}
+
+impl<C> Comp<C>
+where
+    C: 'static + Component,
+{
+    /// Consult `can_leave` with the next route before navigating by a routed link,
+    /// `spair::navigate` or the back/forward buttons. The navigation is cancelled if it
+    /// returns `false`. A navigation from a callback is done after the callback returns, so
+    /// `can_leave` sees the state that the callback left.
+    pub fn guard_navigation<R>(&self, can_leave: fn(&C, &R) -> bool) -> NavigationGuard
+    where
+        R: RouteFromUrl + 'static,
+    {
+        setup_routing_handlers();
+        let comp = self.clone();
+        let guard: Rc<GuardFn> = Rc::new(move |url| {
+            let next = R::from_url(url);
+            comp.with_state(|state| can_leave(state, &next))
+                .unwrap_or_else(|| {
+                    log::error!("A navigation guard can not read its component's state");
+                    false
+                })
+        });
+        GUARDS.with(|guards| {
+            let mut guards = guards.borrow_mut();
+            guards.retain(|guard| guard.strong_count() > 0);
+            guards.push(Rc::downgrade(&guard));
+        });
+        NavigationGuard { _guard: guard }
+    }
+
+    /// Ask the browser to confirm before the page is closed or reloaded if `can_unload`
+    /// returns `false`. `can_unload` is called right in the `beforeunload` event, it is not
+    /// queued like a callback. If the state is busy, the browser is asked to confirm.
+    pub fn guard_unload(&self, can_unload: fn(&C) -> bool) -> ListenerHandle {
+        let comp = self.clone();
+        self.listen_window_now("beforeunload", move |event| {
+            if comp.with_state(can_unload) == Some(true) {
+                return;
+            }
+            event.prevent_default();
+            if let Some(event) = event.dyn_ref::<BeforeUnloadEvent>() {
+                event.set_return_value("");
+            }
+        })
+    }
+}
+
+fn allowed_by_guards(url: &str) -> bool {
+    let guards: Vec<Rc<GuardFn>> =
+        GUARDS.with(|guards| guards.borrow().iter().filter_map(Weak::upgrade).collect());
+    guards.iter().all(|guard| guard(url))
+}
+
+/// Handle the back/forward buttons. The location has already changed, so if a guard cancels
+/// the navigation, the browser is sent back to the entry it came from. The index in the state
+/// of each entry tells which way that is, entries without an index are not guarded.
+fn handle_popstate(event: PopStateEvent) {
+    if IGNORE_NEXT_POPSTATE.replace(false) {
+        return;
+    }
+    let current_index = HISTORY_INDEX.get();
+    let index = event.state().as_f64().map(|index| index as u32);
+    if let Some(index) = index {
+        let href = get_current_location().href().unwrap_or_default();
+        if !allowed_by_guards(&href) {
+            let delta = i64::from(current_index) - i64::from(index);
+            IGNORE_NEXT_POPSTATE.set(true);
+            with_history(|history| history.go_with_delta(delta as i32), "go");
             return;
         }
-        let route = R::from_location(&current_location);
-        LAST_HREF.set(current_href);
-        set_route.call(route);
+    }
+    // An entry that was not added by spair, e.g. by a plain `#fragment` link. It is not known
+    // which way the browser went, so the navigation can not be undone and the guards are not
+    // consulted. The entry is numbered as a new entry after the current one.
+    let index = index.unwrap_or_else(|| {
+        let index = current_index + 1;
+        with_history(
+            |history| history.replace_state(&JsValue::from(index), ""),
+            "replaceState",
+        );
+        index
+    });
+    HISTORY_INDEX.set(index);
+    do_routing();
+}
+
+/// The index of the current history entry, it is kept in the entry's state
+fn init_history_index() {
+    let Ok(history) = helper::WINDOW.with(|window| window.history()) else {
+        return;
     };
-    let do_routing = Rc::new(do_routing);
-    let clone_routing = do_routing.clone();
+    match history.state().ok().and_then(|state| state.as_f64()) {
+        Some(index) => HISTORY_INDEX.set(index as u32),
+        None => with_history(
+            |history| history.replace_state(&JsValue::from(0), ""),
+            "replaceState",
+        ),
+    }
+}
+
+fn setup_routing_handlers() {
+    if HREF_ELEMENT_CLICK_HANDLER.with(|handler| handler.get().is_some()) {
+        return;
+    }
+    LAST_HREF.set(get_current_location().href().unwrap_throw());
+    init_history_index();
+
     let closure = Closure::<dyn Fn(web_sys::MouseEvent)>::new(move |me: web_sys::MouseEvent| {
         me.prevent_default();
         let Some(element) = me.current_target() else {
@@ -56,16 +435,7 @@ pub fn setup_routing<C: 'static + Component, R: 'static + Route>(
         } else {
             return;
         };
-        helper::WINDOW.with(|window| {
-            if let Err(e) = window.history().unwrap_throw().push_state_with_url(
-                &JsValue::NULL,
-                "",
-                Some(href.as_str()),
-            ) {
-                log::error!("Error on push_state_with_url: {e:?}");
-            }
-        });
-        do_routing()
+        navigate_to_url(&href, false);
     });
     if HREF_ELEMENT_CLICK_HANDLER
         .with(|value| value.set(closure))
@@ -73,8 +443,7 @@ pub fn setup_routing<C: 'static + Component, R: 'static + Route>(
     {
         log::error!("Error on storing HREF_ELEMENT_CLICK_HANDLER, why the value is already set?");
     };
-    let do_routing = clone_routing;
-    let closure = Closure::<dyn Fn(web_sys::PopStateEvent)>::new(move |_| do_routing());
+    let closure = Closure::<dyn Fn(PopStateEvent)>::new(handle_popstate);
     helper::WINDOW.with(|window| {
         if let Err(e) = window.add_event_listener_with_callback("popstate", closure.js_function()) {
             log::error!("Error on adding window-popstate-event listener for routing: {e:?}");
@@ -96,8 +465,204 @@ pub(crate) fn add_routing_handler(target: &WsElement) {
 thread_local! {
     static LAST_HREF: RefCell<String> = const{RefCell::new(String::new())};
     static HREF_ELEMENT_CLICK_HANDLER: OnceCell<Closure<dyn Fn(MouseEvent)>> = OnceCell::new();
+    // Routers with the depths of their components, ordered by the depths
+    static ROUTERS: RefCell<Vec<(usize, Weak<RouterFn>)>> = const { RefCell::new(Vec::new()) };
+    static GUARDS: RefCell<Vec<Weak<GuardFn>>> = const { RefCell::new(Vec::new()) };
+    static HISTORY_INDEX: Cell<u32> = const { Cell::new(0) };
+    // Set when a navigation is undone by `history.go`, its `popstate` event is not routed
+    static IGNORE_NEXT_POPSTATE: Cell<bool> = const { Cell::new(false) };
 }
 
 pub fn get_current_location() -> Location {
     helper::WINDOW.with(|window| window.location())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        nested_route_url, percent_decode_path, percent_encode, route_segments, split_route_path,
+    };
+
+    #[test]
+    fn percent_encoding() {
+        assert_eq!(percent_encode("a b&c=d/é~"), "a%20b%26c%3Dd%2F%C3%A9~");
+        assert_eq!(percent_decode_path("a+b%2Fc"), "a+b/c");
+        assert_eq!(percent_decode_path("100%"), "100%");
+        assert_eq!(percent_decode_path("%zz"), "%zz");
+        let value = "a b/c?d#e+f";
+        assert_eq!(percent_decode_path(&percent_encode(value)), value);
+    }
+
+    #[test]
+    fn append_nested_route_url() {
+        assert_eq!(nested_route_url("/settings".to_string(), ""), "/settings");
+        assert_eq!(nested_route_url("/settings".to_string(), "/"), "/settings");
+        assert_eq!(
+            nested_route_url("/settings".to_string(), "/profile"),
+            "/settings/profile"
+        );
+        assert_eq!(
+            nested_route_url("#/settings/".to_string(), "a/b"),
+            "#/settings/a/b"
+        );
+        assert_eq!(nested_route_url(String::new(), ""), "/");
+        assert_eq!(
+            nested_route_url("/search".to_string(), "?q=a"),
+            "/search?q=a"
+        );
+    }
+
+    #[test]
+    fn split_route_path_parts() {
+        assert_eq!(
+            split_route_path("/search?q=a#top"),
+            ("/search", "?q=a", "#top")
+        );
+        assert_eq!(split_route_path("/a#b?c"), ("/a", "", "#b?c"));
+        assert_eq!(split_route_path("users/42"), ("users/42", "", ""));
+    }
+
+    #[test]
+    fn split_route_segments() {
+        assert!(route_segments("/").is_empty());
+        assert!(route_segments("").is_empty());
+        assert_eq!(route_segments("/users/42/"), ["users", "42"]);
+        assert_eq!(route_segments("/users//42"), ["users", "42"]);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[cfg(test)]
+mod browser_tests {
+    use std::cell::RefCell;
+
+    use web_sys::Location;
+
+    use super::{Route, RouteFromUrl, get_current_location, navigate, setup_routing, url_parts};
+    use crate::{
+        WsElement,
+        component::{Component, ComponentViewState, Context, RcComp, create_component},
+        dom::Element,
+    };
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    thread_local! {
+        static NOTIFIED: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn notified(name: &'static str) {
+        NOTIFIED.with(|notified| notified.borrow_mut().push(name));
+    }
+
+    struct TestRoute(String);
+
+    impl Route for TestRoute {
+        fn from_location(location: &Location) -> Self {
+            Self(location.hash().unwrap_or_default())
+        }
+
+        fn url(&self) -> String {
+            self.0.clone()
+        }
+    }
+
+    impl RouteFromUrl for TestRoute {
+        fn from_url(url: &str) -> Self {
+            Self(url_parts(url).2)
+        }
+    }
+
+    struct TestViewState(Element);
+
+    impl ComponentViewState for TestViewState {
+        fn root_element(&self) -> &Element {
+            &self.0
+        }
+    }
+
+    struct Parent {
+        _child: RcComp<Child>,
+    }
+
+    struct Child;
+
+    impl Component for Parent {
+        type ViewState = TestViewState;
+        fn create(_ccontext: &Context<Self>) -> Self::ViewState {
+            TestViewState(WsElement::create_element("div").create_element_with_capacity(0))
+        }
+        fn update(_view_state: &mut Self::ViewState, _ucontext: &Context<Self>) {}
+    }
+
+    impl Component for Child {
+        type ViewState = TestViewState;
+        fn create(_ccontext: &Context<Self>) -> Self::ViewState {
+            TestViewState(WsElement::create_element("span").create_element_with_capacity(0))
+        }
+        fn update(_view_state: &mut Self::ViewState, _ucontext: &Context<Self>) {}
+    }
+
+    #[wasm_bindgen_test]
+    fn parents_are_notified_before_children() {
+        // The child registers its router in the parent's `new_state`, before the parent's
+        // router is registered
+        let parent = create_component(
+            |_| Parent {
+                _child: RcComp::new(|comp| {
+                    comp.setup_nested_routing(
+                        |route: TestRoute| Some(route.0),
+                        |_: &mut Child, _: String| notified("child"),
+                    );
+                    Child
+                }),
+            },
+            |_: &mut Parent, _: TestRoute| notified("parent"),
+            setup_routing,
+        );
+        NOTIFIED.with(|notified| notified.borrow_mut().clear());
+
+        navigate(&TestRoute("#/routing-order".to_string()));
+        assert_eq!(
+            NOTIFIED.with(|notified| notified.borrow().clone()),
+            ["parent", "child"]
+        );
+        drop(parent);
+    }
+
+    struct Form {
+        saved: bool,
+    }
+
+    impl Component for Form {
+        type ViewState = TestViewState;
+        fn create(_ccontext: &Context<Self>) -> Self::ViewState {
+            TestViewState(WsElement::create_element("form").create_element_with_capacity(0))
+        }
+        fn update(_view_state: &mut Self::ViewState, _ucontext: &Context<Self>) {}
+    }
+
+    fn current_hash() -> String {
+        get_current_location().hash().unwrap_or_default()
+    }
+
+    #[wasm_bindgen_test]
+    fn guard_navigation() {
+        let form = RcComp::new(|_| Form { saved: false });
+        let comp = form.comp();
+        let _guard = comp
+            .guard_navigation(|state: &Form, next: &TestRoute| state.saved || next.0 == "#/help");
+        let hash = current_hash();
+        navigate(&TestRoute("#/blocked".to_string()));
+        assert_eq!(current_hash(), hash);
+        navigate(&TestRoute("#/help".to_string()));
+        assert_eq!(current_hash(), "#/help");
+
+        // The guard is consulted after the callback returns, with the state it left
+        comp.callback(|state| {
+            state.saved = true;
+            navigate(&TestRoute("#/saved".to_string()));
+        })
+        .call();
+        assert_eq!(current_hash(), "#/saved");
+    }
+}
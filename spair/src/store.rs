@@ -0,0 +1,118 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    component::{
+        Callback, CallbackArg, Comp, Component, ShouldRender, execute_then_the_update_queue,
+    },
+    context::Subscribers,
+};
+
+/// Shared state for many components. Components subscribe to a store to be notified when
+/// the state is mutated.
+pub struct Store<T>(Rc<StoreData<T>>);
+
+struct StoreData<T> {
+    state: RefCell<T>,
+    subscribers: Subscribers<()>,
+}
+
+/// Returned by `Store::subscribe`, the subscription is removed when this is dropped.
+#[must_use = "the subscription is removed when the handle is dropped"]
+pub struct SubscriptionHandle {
+    unsubscribe: Option<Box<dyn FnOnce()>>,
+}
+
+impl SubscriptionHandle {
+    pub(crate) fn new(unsubscribe: impl FnOnce() + 'static) -> Self {
+        Self {
+            unsubscribe: Some(Box::new(unsubscribe)),
+        }
+    }
+}
+
+impl Drop for SubscriptionHandle {
+    fn drop(&mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            unsubscribe();
+        }
+    }
+}
+
+impl<T> Clone for Store<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: 'static> Store<T> {
+    pub fn new(state: T) -> Self {
+        Self(Rc::new(StoreData {
+            state: RefCell::new(state),
+            subscribers: Subscribers::default(),
+        }))
+    }
+
+    pub fn read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.0.state.borrow())
+    }
+
+    /// Mutate the state, then notify all subscribers. The subscribed components are updated
+    /// in the same pass of the update queue.
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        execute_then_the_update_queue(|| {
+            match self.0.state.try_borrow_mut() {
+                Ok(mut state) => f(&mut state),
+                Err(e) => {
+                    log::error!("Error on borrowing the state of a store to update: {e}");
+                    return;
+                }
+            }
+            self.0.subscribers.notify(&());
+        });
+    }
+
+    pub fn callback(&self, f: impl Fn(&mut T) + 'static) -> Callback {
+        let store = self.clone();
+        Callback::from_fn(move || store.update(&f))
+    }
+
+    pub fn callback_arg<A: 'static>(&self, f: impl Fn(&mut T, A) + 'static) -> CallbackArg<A> {
+        let store = self.clone();
+        CallbackArg::from_fn(move |arg| store.update(|state| f(state, arg)))
+    }
+
+    /// Execute `map` on the component now and every time the state is mutated. Keep the
+    /// returned handle in the component's state: the subscription is removed when the handle
+    /// is dropped, or when the component is unmounted.
+    pub fn subscribe<C, S>(&self, comp: &Comp<C>, map: fn(&mut C, &T) -> S) -> SubscriptionHandle
+    where
+        C: 'static + Component,
+        S: 'static + Into<ShouldRender>,
+    {
+        // Weak, because the store keeps the subscriber
+        let store: Weak<StoreData<T>> = Rc::downgrade(&self.0);
+        let callback = comp.callback_arg(move |state: &mut C, _: ()| match store.upgrade() {
+            Some(store) => map(state, &store.state.borrow()).into(),
+            None => ShouldRender::No,
+        });
+        callback.call(());
+
+        let comp = comp.clone();
+        let id = self.0.subscribers.add(Box::new(move |_| {
+            if !comp.is_alive() {
+                return false;
+            }
+            callback.call(());
+            true
+        }));
+        let store = Rc::downgrade(&self.0);
+        SubscriptionHandle::new(move || {
+            if let Some(store) = store.upgrade() {
+                store.subscribers.remove(id);
+            }
+        })
+    }
+}
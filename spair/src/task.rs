@@ -0,0 +1,101 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::{Context, Poll, Waker},
+};
+
+#[derive(Default)]
+struct TaskState {
+    aborted: Cell<bool>,
+    finished: Cell<bool>,
+    waker: RefCell<Option<Waker>>,
+}
+
+/// A handle to a task started by `Comp::spawn`. The task is aborted when the handle is dropped.
+#[must_use = "the task is aborted when its handle is dropped"]
+pub struct TaskHandle(Rc<TaskState>);
+
+impl TaskHandle {
+    /// Abort the task, its future will be dropped without being polled again.
+    pub fn abort(&self) {
+        self.0.aborted.set(true);
+        if let Some(waker) = self.0.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// `true` if the task has completed or has been aborted.
+    pub fn is_finished(&self) -> bool {
+        self.0.finished.get() || self.0.aborted.get()
+    }
+}
+
+impl Drop for TaskHandle {
+    fn drop(&mut self) {
+        self.abort();
+    }
+}
+
+struct Abortable {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    state: Rc<TaskState>,
+}
+
+impl Future for Abortable {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.state.aborted.get() {
+            return Poll::Ready(());
+        }
+        *self.state.waker.borrow_mut() = Some(cx.waker().clone());
+        let poll = self.future.as_mut().poll(cx);
+        if poll.is_ready() {
+            self.state.finished.set(true);
+        }
+        poll
+    }
+}
+
+pub(crate) fn spawn(future: impl Future<Output = ()> + 'static) -> TaskHandle {
+    let state = Rc::new(TaskState::default());
+    wasm_bindgen_futures::spawn_local(Abortable {
+        future: Box::pin(future),
+        state: state.clone(),
+    });
+    TaskHandle(state)
+}
+
+/// Tasks of a component, they are aborted when the component is unmounted.
+#[derive(Default)]
+pub(crate) struct Tasks {
+    tasks: Vec<Weak<TaskState>>,
+    latest: HashMap<&'static str, TaskHandle>,
+}
+
+impl Tasks {
+    pub fn add(&mut self, handle: &TaskHandle) {
+        self.tasks
+            .retain(|task| task.upgrade().is_some_and(|task| !task.finished.get()));
+        self.tasks.push(Rc::downgrade(&handle.0));
+    }
+
+    /// Keep the handle, the previous task with the same key is aborted.
+    pub fn set_latest(&mut self, key: &'static str, handle: TaskHandle) {
+        self.latest.insert(key, handle);
+    }
+
+    pub fn abort_latest(&mut self, key: &'static str) {
+        self.latest.remove(key);
+    }
+
+    pub fn abort_all(&mut self) {
+        for task in self.tasks.drain(..).filter_map(|task| task.upgrade()) {
+            TaskHandle(task).abort();
+        }
+        self.latest.clear();
+    }
+}
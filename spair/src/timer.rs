@@ -0,0 +1,142 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use wasm_bindgen::{JsCast, closure::Closure};
+
+use crate::{
+    component::{Comp, Component, ShouldRender},
+    helper::WINDOW,
+};
+
+/// A handle to a timer started by `Comp::set_timeout`, `Comp::set_interval` or
+/// `Comp::request_animation_frame_loop`. The timer is cancelled when the handle is dropped.
+#[must_use = "the timer is cancelled when its handle is dropped"]
+pub struct TimerHandle(Timer);
+
+enum Timer {
+    Timeout {
+        id: i32,
+        _closure: Closure<dyn FnMut()>,
+    },
+    Interval {
+        id: i32,
+        _closure: Closure<dyn FnMut()>,
+    },
+    AnimationFrameLoop(Rc<FrameLoop>),
+}
+
+type FrameClosure = Closure<dyn FnMut(f64)>;
+struct FrameLoop {
+    id: Cell<i32>,
+    closure: RefCell<Option<FrameClosure>>,
+}
+
+impl FrameLoop {
+    fn request_next_frame(&self) {
+        let closure = self.closure.borrow();
+        let Some(closure) = closure.as_ref() else {
+            return;
+        };
+        match WINDOW.with(|window| window.request_animation_frame(closure.as_ref().unchecked_ref()))
+        {
+            Ok(id) => self.id.set(id),
+            Err(e) => log::error!("Error on requesting an animation frame: {e:?}"),
+        }
+    }
+}
+
+impl Drop for TimerHandle {
+    fn drop(&mut self) {
+        WINDOW.with(|window| match &self.0 {
+            Timer::Timeout { id, .. } => window.clear_timeout_with_handle(*id),
+            Timer::Interval { id, .. } => window.clear_interval_with_handle(*id),
+            Timer::AnimationFrameLoop(frame_loop) => {
+                if let Err(e) = window.cancel_animation_frame(frame_loop.id.get()) {
+                    log::error!("Error on cancelling an animation frame: {e:?}");
+                }
+                // Without the closure, a running frame can not request the next frame
+                frame_loop.closure.take();
+            }
+        });
+    }
+}
+
+impl<C> Comp<C>
+where
+    C: 'static + Component,
+{
+    /// Execute `handler` once after `ms` milliseconds.
+    pub fn set_timeout<S>(&self, ms: i32, handler: impl Fn(&mut C) -> S + 'static) -> TimerHandle
+    where
+        S: Into<ShouldRender>,
+    {
+        let callback = self.callback(handler);
+        let closure = Closure::<dyn FnMut()>::new(move || callback.call());
+        let id = WINDOW
+            .with(|window| {
+                window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                    closure.as_ref().unchecked_ref(),
+                    ms,
+                )
+            })
+            .unwrap_or_else(|e| {
+                log::error!("Error on setting a timeout: {e:?}");
+                0
+            });
+        TimerHandle(Timer::Timeout {
+            id,
+            _closure: closure,
+        })
+    }
+
+    /// Execute `handler` every `ms` milliseconds.
+    pub fn set_interval<S>(&self, ms: i32, handler: impl Fn(&mut C) -> S + 'static) -> TimerHandle
+    where
+        S: Into<ShouldRender>,
+    {
+        let callback = self.callback(handler);
+        let closure = Closure::<dyn FnMut()>::new(move || callback.call());
+        let id = WINDOW
+            .with(|window| {
+                window.set_interval_with_callback_and_timeout_and_arguments_0(
+                    closure.as_ref().unchecked_ref(),
+                    ms,
+                )
+            })
+            .unwrap_or_else(|e| {
+                log::error!("Error on setting an interval: {e:?}");
+                0
+            });
+        TimerHandle(Timer::Interval {
+            id,
+            _closure: closure,
+        })
+    }
+
+    /// Execute `handler` on every animation frame, with the timestamp of the frame.
+    pub fn request_animation_frame_loop<S>(
+        &self,
+        handler: impl Fn(&mut C, f64) -> S + 'static,
+    ) -> TimerHandle
+    where
+        S: Into<ShouldRender>,
+    {
+        let callback = self.callback_arg(handler);
+        let frame_loop = Rc::new(FrameLoop {
+            id: Cell::new(0),
+            closure: RefCell::new(None),
+        });
+        let weak_frame_loop = Rc::downgrade(&frame_loop);
+        let closure = FrameClosure::new(move |timestamp| {
+            callback.call(timestamp);
+            if let Some(frame_loop) = weak_frame_loop.upgrade() {
+                frame_loop.request_next_frame();
+            }
+        });
+        *frame_loop.closure.borrow_mut() = Some(closure);
+        frame_loop.request_next_frame();
+        TimerHandle(Timer::AnimationFrameLoop(frame_loop))
+    }
+}
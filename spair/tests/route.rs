@@ -0,0 +1,161 @@
+use spair::{NestedRoute, Route};
+
+#[derive(Debug, PartialEq, spair::Query)]
+struct SearchQuery {
+    q: String,
+    page: Option<u32>,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, spair::Query)]
+struct Anchor {
+    section: Option<String>,
+}
+
+#[derive(Debug, PartialEq, spair::Route)]
+enum AdminRoute {
+    #[route("/")]
+    Dashboard,
+    #[route("/users/:id")]
+    User { id: u32 },
+    #[not_found]
+    NotFound,
+}
+
+#[derive(Debug, PartialEq, spair::Route)]
+enum AppRoute {
+    #[route("/")]
+    Home,
+    #[route("/users/:id/posts/:post_id")]
+    Post { id: u32, post_id: u64 },
+    #[route("/files/:name")]
+    File(String),
+    #[route("#/settings/:tab")]
+    Settings(String),
+    #[route("/admin/*rest")]
+    Admin(AdminRoute),
+    #[route("/search")]
+    Search {
+        #[query]
+        query: SearchQuery,
+        #[fragment]
+        anchor: Anchor,
+    },
+    #[not_found]
+    NotFound,
+}
+
+fn all_routes() -> Vec<AppRoute> {
+    vec![
+        AppRoute::Home,
+        AppRoute::Post { id: 1, post_id: 2 },
+        AppRoute::File("a b/c?d#e+f".to_string()),
+        AppRoute::Settings("profile".to_string()),
+        AppRoute::Admin(AdminRoute::Dashboard),
+        AppRoute::Admin(AdminRoute::User { id: 7 }),
+        AppRoute::Search {
+            query: SearchQuery {
+                q: "rust & wasm".to_string(),
+                page: Some(2),
+                tags: vec!["a".to_string(), "b c".to_string()],
+            },
+            anchor: Anchor {
+                section: Some("top".to_string()),
+            },
+        },
+        AppRoute::Search {
+            query: SearchQuery {
+                q: String::new(),
+                page: None,
+                tags: Vec::new(),
+            },
+            anchor: Anchor { section: None },
+        },
+    ]
+}
+
+#[test]
+fn from_path_round_trips() {
+    for route in all_routes() {
+        assert_eq!(
+            AppRoute::from_path(&route.path()),
+            route,
+            "{}",
+            route.path()
+        );
+    }
+}
+
+#[test]
+fn urls() {
+    assert_eq!(AppRoute::Home.url(), "/");
+    assert_eq!(
+        AppRoute::Post { id: 1, post_id: 2 }.url(),
+        "/users/1/posts/2"
+    );
+    assert_eq!(
+        AppRoute::File("a b/c".to_string()).url(),
+        "/files/a%20b%2Fc"
+    );
+    assert_eq!(
+        AppRoute::Settings("profile".to_string()).url(),
+        "#/settings/profile"
+    );
+    assert_eq!(
+        AppRoute::Admin(AdminRoute::User { id: 7 }).url(),
+        "/admin/users/7"
+    );
+    assert_eq!(AppRoute::Admin(AdminRoute::Dashboard).url(), "/admin");
+    assert_eq!(
+        all_routes()[6].url(),
+        "/search?q=rust%20%26%20wasm&page=2&tags=a&tags=b%20c#section=top"
+    );
+    assert_eq!(AppRoute::NotFound.url(), "");
+}
+
+#[test]
+fn not_found() {
+    assert_eq!(AppRoute::from_path("/nowhere"), AppRoute::NotFound);
+    // `id` is not a `u32`
+    assert_eq!(AppRoute::from_path("/users/x/posts/2"), AppRoute::NotFound);
+    // `q` is required
+    assert_eq!(AppRoute::from_path("/search?page=2"), AppRoute::NotFound);
+    assert_eq!(
+        AppRoute::from_path("/admin/nowhere"),
+        AppRoute::Admin(AdminRoute::NotFound)
+    );
+    assert_eq!(AdminRoute::from_path("/users/x"), AdminRoute::NotFound);
+}
+
+/// The generated code must not depend on the names in scope at the derive.
+mod shadowed_names {
+    #![allow(dead_code, unused_macros)]
+
+    struct String;
+    struct Ok;
+    type Result = ();
+    macro_rules! format {
+        ($($tokens:tt)*) => {
+            compile_error!("the shadowed `format!` is used")
+        };
+    }
+
+    #[derive(spair::Query)]
+    struct Filter {
+        tag: Option<std::string::String>,
+    }
+
+    #[derive(spair::Route)]
+    enum Shadowed {
+        #[route("/items/:id")]
+        Item {
+            id: u32,
+            #[query]
+            filter: Filter,
+        },
+        #[route("/nested/*rest")]
+        Nested(std::string::String),
+        #[not_found]
+        NotFound,
+    }
+}
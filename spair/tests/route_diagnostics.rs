@@ -0,0 +1,8 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+#[test]
+fn route_diagnostics() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/fail/route_*.rs");
+    cases.pass("tests/ui/pass/route_*.rs");
+}
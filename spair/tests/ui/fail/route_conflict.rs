@@ -0,0 +1,11 @@
+#[derive(spair::Route)]
+enum AppRoute {
+    #[route("/posts/:id")]
+    Post(u32),
+    #[route("/posts/:post_id")]
+    OtherPost { post_id: u32 },
+    #[not_found]
+    NotFound,
+}
+
+fn main() {}
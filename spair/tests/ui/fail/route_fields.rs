@@ -0,0 +1,18 @@
+#[derive(spair::Route)]
+enum AppRoute {
+    #[route("/users/:id")]
+    User { user_id: u32 },
+    #[route("/posts/:id/:comment")]
+    Comment(u32),
+    #[route("/about/:page")]
+    About,
+    #[route("#/settings")]
+    Settings {
+        #[fragment]
+        anchor: String,
+    },
+    #[not_found]
+    NotFound,
+}
+
+fn main() {}
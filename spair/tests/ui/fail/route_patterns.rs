@@ -0,0 +1,24 @@
+#[derive(spair::Route)]
+enum AppRoute {
+    #[route("users")]
+    Users,
+    #[route("/search?q")]
+    Search,
+    #[route("/files/*rest/more")]
+    Files(String),
+    #[route("/a/:id/:id")]
+    Twice(u32, u32),
+    #[route("/b/x:y")]
+    Misplaced,
+    Unrouted,
+    #[not_found]
+    NotFound(u32),
+}
+
+#[derive(spair::Route)]
+enum NoNotFound {
+    #[route("/")]
+    Home,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+#[derive(spair::Route)]
+enum AppRoute {
+    #[route("/users/:name")]
+    User(String),
+    // Always matched by `/users/:name` first
+    #[route("/users/new")]
+    NewUser,
+    #[route("/files/*rest")]
+    Files(String),
+    #[route("/files/readme")]
+    Readme,
+    #[not_found]
+    NotFound,
+}
+
+fn main() {}
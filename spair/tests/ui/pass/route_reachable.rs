@@ -0,0 +1,14 @@
+// A parameter that may fail to parse does not hide the patterns after it
+#[derive(spair::Route)]
+enum AppRoute {
+    #[route("/posts/:id")]
+    Post(u32),
+    #[route("/posts/new")]
+    NewPost,
+    #[route("/posts/:slug")]
+    PostBySlug(String),
+    #[not_found]
+    NotFound,
+}
+
+fn main() {}
@@ -5,6 +5,8 @@ TARGET="--target=wasm32-unknown-unknown"
 
 cargo fmt --all -- --check
 cargo clippy --all -- -D warnings
+# Native tests, including the compile-fail tests of the derive macros
+cargo test -p spair
 
 cd spair
 # --chrome and --firefox on separate lines to easily disable one of them if the driver has problems